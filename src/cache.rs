@@ -1,7 +1,7 @@
 use std::fs;
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
 use anyhow::{Context, Result};
 use rusqlite::{OptionalExtension, params};
@@ -10,6 +10,7 @@ use serde::de::DeserializeOwned;
 use tokio::task;
 
 use crate::config::{CacheBackend, CacheSettings};
+use crate::metrics::metrics;
 
 const CACHE_TABLE_SQL: &str = r#"
 CREATE TABLE IF NOT EXISTS cache_entries (
@@ -120,8 +121,9 @@ impl SqliteCacheStore {
         let path = self.path.clone();
         let namespace = namespace.to_string();
         let key = key.to_string();
+        let timer = CacheOperationTimer::start("get");
 
-        task::spawn_blocking(move || -> Result<Option<String>> {
+        let result = task::spawn_blocking(move || -> Result<Option<String>> {
             let now = now_unix_seconds();
             let conn = rusqlite::Connection::open(&path)
                 .with_context(|| format!("failed to open sqlite cache DB at {}", path.display()))?;
@@ -151,7 +153,9 @@ impl SqliteCacheStore {
             Ok(Some(value))
         })
         .await
-        .context("sqlite get task join failed")?
+        .context("sqlite get task join failed")?;
+        timer.finish();
+        result
     }
 
     async fn set(
@@ -165,6 +169,7 @@ impl SqliteCacheStore {
         let namespace = namespace.to_string();
         let key = key.to_string();
         let value = value.to_string();
+        let timer = CacheOperationTimer::start("set");
 
         task::spawn_blocking(move || -> Result<()> {
             let now = now_unix_seconds();
@@ -195,11 +200,33 @@ impl SqliteCacheStore {
         })
         .await
         .context("sqlite set task join failed")??;
+        timer.finish();
 
         Ok(())
     }
 }
 
+struct CacheOperationTimer {
+    operation: &'static str,
+    started: Instant,
+}
+
+impl CacheOperationTimer {
+    fn start(operation: &'static str) -> Self {
+        Self {
+            operation,
+            started: Instant::now(),
+        }
+    }
+
+    fn finish(self) {
+        metrics()
+            .cache_operation_seconds
+            .with_label_values(&[self.operation])
+            .observe(self.started.elapsed().as_secs_f64());
+    }
+}
+
 fn now_unix_seconds() -> i64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -1,10 +1,18 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Debug, Formatter};
 use std::fs;
+use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use anyhow::{Context, Result, bail};
+use chrono::{NaiveTime, Weekday};
+use chrono_tz::Tz;
+use regex::Regex;
 use serde::Deserialize;
+use statushub::signing;
+
+use crate::event::{DiscordStatus, EventKind};
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Settings {
@@ -20,34 +28,208 @@ pub struct Settings {
     pub cache: CacheSettings,
     #[serde(default)]
     pub state_cache: StateCacheSettings,
+    #[serde(default)]
+    pub outbox: OutboxSettings,
+    #[serde(default)]
+    pub history: HistorySettings,
+    #[serde(default)]
+    pub digest: DigestSettings,
+    #[serde(default)]
+    pub http: HttpSettings,
+    #[serde(default)]
+    pub rules: Vec<RuleSettings>,
+    #[serde(default)]
+    pub quiet_hours: QuietHoursSettings,
 }
 
 impl Settings {
+    /// Reads, interpolates and validates the configuration file. `${NAME}` in any
+    /// string value is replaced by the environment variable `NAME` (`$${` keeps a
+    /// literal `${`), and `*_file` secrets are read from their files.
     pub fn load_from_path(path: impl AsRef<Path>) -> Result<Self> {
         let path = path.as_ref();
         let raw = fs::read_to_string(path)
             .with_context(|| format!("failed to read config file from {}", path.display()))?;
-        let settings: Self =
+        let mut value: toml::Value =
             toml::from_str(&raw).with_context(|| format!("failed to parse {}", path.display()))?;
+        interpolate_env(&mut value, "", &|name| std::env::var(name).ok())?;
+        let mut settings: Self = value
+            .try_into()
+            .with_context(|| format!("failed to parse {}", path.display()))?;
+        settings.resolve_secret_files()?;
         settings.validate()?;
         Ok(settings)
     }
 
+    fn resolve_secret_files(&mut self) -> Result<()> {
+        let discord = &mut self.discord;
+        let mut bot_token =
+            Some(std::mem::take(&mut discord.bot_token)).filter(|token| !token.expose().is_empty());
+        resolve_secret_file(
+            "discord.bot_token",
+            &mut bot_token,
+            discord.bot_token_file.take(),
+        )?;
+        discord.bot_token = bot_token.unwrap_or_default();
+
+        let webhook = &mut self.webhook;
+        resolve_secret_file(
+            "webhook.token",
+            &mut webhook.token,
+            webhook.token_file.take(),
+        )?;
+        if let Some(signing) = webhook.signing.as_mut() {
+            signing.resolve_secret_file("webhook.signing")?;
+        }
+        for (index, sink) in webhook.sinks.iter_mut().enumerate() {
+            let field = format!("webhook.sinks[{index}]");
+            resolve_secret_file(
+                &format!("{field}.token"),
+                &mut sink.token,
+                sink.token_file.take(),
+            )?;
+            if let Some(signing) = sink.signing.as_mut() {
+                signing.resolve_secret_file(&format!("{field}.signing"))?;
+            }
+        }
+
+        resolve_secret_file(
+            "steam.api_key",
+            &mut self.steam.api_key,
+            self.steam.api_key_file.take(),
+        )
+    }
+
     fn validate(&self) -> Result<()> {
         self.discord.validate()?;
         self.webhook.validate()?;
+        self.message.validate("message")?;
         self.steam.validate()?;
         self.reminder.validate()?;
         self.cache.validate()?;
         self.state_cache.validate()?;
+        self.outbox.validate()?;
+        self.history.validate()?;
+        self.digest.validate()?;
+        if self.digest.enabled && !self.history.enabled {
+            bail!("digest.enabled=true requires history.enabled=true");
+        }
+        let sink_names: HashSet<String> = self
+            .webhook
+            .sinks()
+            .into_iter()
+            .map(|sink| sink.name)
+            .collect();
+        for (index, rule) in self.rules.iter().enumerate() {
+            rule.validate(&format!("rules[{index}]"), &sink_names)?;
+        }
+        self.quiet_hours.validate()?;
         Ok(())
     }
 }
 
+/// A secret configuration value; `Debug` output is redacted.
+#[derive(Clone, Default, Deserialize)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Debug for Secret {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("\"<redacted>\"")
+    }
+}
+
+/// Fills `value` from a `*_file` setting, dropping the trailing newline.
+fn resolve_secret_file(
+    field: &str,
+    value: &mut Option<Secret>,
+    file: Option<PathBuf>,
+) -> Result<()> {
+    let Some(path) = file else {
+        return Ok(());
+    };
+    if value.is_some() {
+        bail!("{field} and {field}_file cannot be used together");
+    }
+    let raw = fs::read_to_string(&path)
+        .with_context(|| format!("failed to read {field}_file from {}", path.display()))?;
+    *value = Some(Secret(raw.trim_end_matches(['\r', '\n']).to_string()));
+    Ok(())
+}
+
+/// Replaces `${NAME}` in every string value with `lookup(NAME)`.
+fn interpolate_env(
+    value: &mut toml::Value,
+    field: &str,
+    lookup: &dyn Fn(&str) -> Option<String>,
+) -> Result<()> {
+    match value {
+        toml::Value::String(raw) if raw.contains('$') => {
+            *raw = expand_env(raw, lookup).with_context(|| format!("invalid {field}"))?;
+        }
+        toml::Value::Array(items) => {
+            for (index, item) in items.iter_mut().enumerate() {
+                interpolate_env(item, &format!("{field}[{index}]"), lookup)?;
+            }
+        }
+        toml::Value::Table(table) => {
+            for (key, item) in table.iter_mut() {
+                let field = if field.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{field}.{key}")
+                };
+                interpolate_env(item, &field, lookup)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn expand_env(raw: &str, lookup: &dyn Fn(&str) -> Option<String>) -> Result<String> {
+    let mut expanded = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        if let Some(escaped) = after.strip_prefix("${") {
+            expanded.push_str("${");
+            rest = escaped;
+        } else if let Some(reference) = after.strip_prefix('{') {
+            let Some(end) = reference.find('}') else {
+                bail!("unterminated ${{ in {raw:?}");
+            };
+            let name = &reference[..end];
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                bail!("invalid environment variable name {name:?}");
+            }
+            let value =
+                lookup(name).with_context(|| format!("environment variable {name} is not set"))?;
+            expanded.push_str(&value);
+            rest = &reference[end + 1..];
+        } else {
+            expanded.push('$');
+            rest = after;
+        }
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct DiscordSettings {
-    pub bot_token: String,
-    pub user_id: u64,
+    #[serde(default)]
+    pub bot_token: Secret,
+    /// Reads `bot_token` from this file instead.
+    pub bot_token_file: Option<PathBuf>,
+    pub user_id: Option<u64>,
     pub guild_id: Option<u64>,
     #[serde(default)]
     pub emit_initial_status: bool,
@@ -55,24 +237,130 @@ pub struct DiscordSettings {
     pub emit_on_activity_change: bool,
     #[serde(default)]
     pub rich_presence_only: bool,
+    #[serde(default)]
+    pub emit_on_platform_change: bool,
+    #[serde(default)]
+    pub emit_on_custom_status_change: bool,
+    #[serde(default)]
+    pub emit_on_track_change: bool,
+    #[serde(default)]
+    pub track_change_min_interval_seconds: u64,
+    #[serde(default)]
+    pub status_debounce_seconds: u64,
+    #[serde(default)]
+    pub activity_debounce_seconds: u64,
+    #[serde(default)]
+    pub targets: Vec<DiscordTargetSettings>,
 }
 
 impl DiscordSettings {
     fn validate(&self) -> Result<()> {
-        if self.bot_token.trim().is_empty() {
-            bail!("discord.bot_token cannot be empty");
+        if self.bot_token.expose().trim().is_empty() {
+            bail!("discord.bot_token (or discord.bot_token_file) cannot be empty");
+        }
+        if self.user_id.is_some() && !self.targets.is_empty() {
+            bail!("discord.user_id and discord.targets cannot be used together");
+        }
+        if self.user_id.is_none() && self.targets.is_empty() {
+            bail!("either discord.user_id or at least one [[discord.targets]] must be provided");
+        }
+
+        let targets = self.targets();
+        let mut seen = HashSet::new();
+        for (index, target) in targets.iter().enumerate() {
+            let field = if self.targets.is_empty() {
+                "discord".to_string()
+            } else {
+                format!("discord.targets[{index}]")
+            };
+            target.validate(&field)?;
+            if !seen.insert((target.user_id, target.guild_id)) {
+                bail!(
+                    "{field} duplicates user_id={} guild_id={:?}",
+                    target.user_id,
+                    target.guild_id
+                );
+            }
         }
+        Ok(())
+    }
+
+    /// Returns the monitored targets, mapping the legacy single-user fields to one target.
+    pub fn targets(&self) -> Vec<DiscordTargetSettings> {
+        match self.user_id {
+            Some(user_id) => vec![DiscordTargetSettings {
+                user_id,
+                guild_id: self.guild_id,
+                emit_initial_status: self.emit_initial_status,
+                emit_on_activity_change: self.emit_on_activity_change,
+                rich_presence_only: self.rich_presence_only,
+                emit_on_platform_change: self.emit_on_platform_change,
+                emit_on_custom_status_change: self.emit_on_custom_status_change,
+                emit_on_track_change: self.emit_on_track_change,
+                track_change_min_interval_seconds: self.track_change_min_interval_seconds,
+                status_debounce_seconds: self.status_debounce_seconds,
+                activity_debounce_seconds: self.activity_debounce_seconds,
+                reminder: None,
+            }],
+            None => self.targets.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DiscordTargetSettings {
+    pub user_id: u64,
+    pub guild_id: Option<u64>,
+    #[serde(default)]
+    pub emit_initial_status: bool,
+    #[serde(default = "default_emit_on_activity_change")]
+    pub emit_on_activity_change: bool,
+    #[serde(default)]
+    pub rich_presence_only: bool,
+    /// Emit when the set of platforms (desktop / mobile / web) or their statuses
+    /// change while the overall status stays the same.
+    #[serde(default)]
+    pub emit_on_platform_change: bool,
+    /// Emit when the custom status (emoji / text) changes on its own.
+    #[serde(default)]
+    pub emit_on_custom_status_change: bool,
+    /// Emit when the Spotify track changes. Track changes then no longer count as
+    /// activity changes; starting or stopping listening still does.
+    #[serde(default)]
+    pub emit_on_track_change: bool,
+    /// Minimum time between two track change events; changes in between are dropped.
+    #[serde(default)]
+    pub track_change_min_interval_seconds: u64,
+    /// A status change must stay stable this long before it is emitted; changes in
+    /// between are coalesced into one event. 0 disables debouncing.
+    #[serde(default)]
+    pub status_debounce_seconds: u64,
+    /// Same as `status_debounce_seconds`, for activity, platform, custom status and
+    /// track changes while the status stays the same.
+    #[serde(default)]
+    pub activity_debounce_seconds: u64,
+    /// Overrides the global `[reminder]` section for this target.
+    pub reminder: Option<ReminderSettings>,
+}
+
+impl DiscordTargetSettings {
+    fn validate(&self, field: &str) -> Result<()> {
         if self.user_id == 0 {
-            bail!("discord.user_id must be greater than 0");
+            bail!("{field}.user_id must be greater than 0");
         }
         if self.guild_id.is_some_and(|guild_id| guild_id == 0) {
-            bail!("discord.guild_id must be greater than 0 when provided");
+            bail!("{field}.guild_id must be greater than 0 when provided");
         }
         if self.rich_presence_only && !self.emit_on_activity_change {
             bail!(
-                "discord.emit_on_activity_change must be true when discord.rich_presence_only=true"
+                "{field}.emit_on_activity_change must be true when {field}.rich_presence_only=true"
             );
         }
+        if let Some(reminder) = self.reminder.as_ref() {
+            reminder
+                .validate()
+                .with_context(|| format!("invalid {field}.reminder"))?;
+        }
         Ok(())
     }
 }
@@ -81,25 +369,111 @@ impl DiscordSettings {
 pub struct WebhookSettings {
     #[serde(default = "default_webhook_mode")]
     pub mode: WebhookMode,
-    pub url: String,
-    pub token: Option<String>,
+    pub url: Option<String>,
+    pub token: Option<Secret>,
+    pub token_file: Option<PathBuf>,
     #[serde(default)]
     pub headers: HashMap<String, String>,
     #[serde(default = "default_timeout_seconds")]
     pub timeout_seconds: u64,
     #[serde(default)]
     pub openclaw: OpenClawSettings,
+    pub signing: Option<WebhookSigningSettings>,
+    #[serde(default)]
+    pub sinks: Vec<WebhookSinkSettings>,
 }
 
 impl WebhookSettings {
     fn validate(&self) -> Result<()> {
+        if self.url.is_some() && !self.sinks.is_empty() {
+            bail!("webhook.url and webhook.sinks cannot be used together");
+        }
+        if self.url.is_none() && self.sinks.is_empty() {
+            bail!("either webhook.url or at least one [[webhook.sinks]] must be provided");
+        }
+
+        let mut names = HashSet::new();
+        for (index, sink) in self.sinks().iter().enumerate() {
+            let field = if self.sinks.is_empty() {
+                "webhook".to_string()
+            } else {
+                format!("webhook.sinks[{index}]")
+            };
+            sink.validate(&field)?;
+            if !names.insert(sink.name.clone()) {
+                bail!("{field}.name duplicates sink name {}", sink.name);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the configured sinks, mapping the legacy single-webhook fields to a
+    /// sink named `default` that receives every event kind.
+    pub fn sinks(&self) -> Vec<WebhookSinkSettings> {
+        match self.url.as_ref() {
+            Some(url) => vec![WebhookSinkSettings {
+                name: DEFAULT_SINK_NAME.to_string(),
+                mode: self.mode,
+                url: url.clone(),
+                token: self.token.clone(),
+                token_file: None,
+                headers: self.headers.clone(),
+                timeout_seconds: self.timeout_seconds,
+                openclaw: self.openclaw.clone(),
+                signing: self.signing.clone(),
+                message: None,
+                events: default_sink_events(),
+            }],
+            None => self.sinks.clone(),
+        }
+    }
+}
+
+pub const DEFAULT_SINK_NAME: &str = "default";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebhookSinkSettings {
+    pub name: String,
+    #[serde(default = "default_webhook_mode")]
+    pub mode: WebhookMode,
+    pub url: String,
+    pub token: Option<Secret>,
+    /// Reads `token` from this file instead.
+    pub token_file: Option<PathBuf>,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    #[serde(default = "default_timeout_seconds")]
+    pub timeout_seconds: u64,
+    #[serde(default)]
+    pub openclaw: OpenClawSettings,
+    pub signing: Option<WebhookSigningSettings>,
+    /// Overrides the global `[message]` section for this sink.
+    pub message: Option<MessageTemplateSettings>,
+    #[serde(default = "default_sink_events")]
+    pub events: Vec<EventKind>,
+}
+
+impl WebhookSinkSettings {
+    fn validate(&self, field: &str) -> Result<()> {
+        if self.name.trim().is_empty() {
+            bail!("{field}.name cannot be empty");
+        }
         if self.url.trim().is_empty() {
-            bail!("webhook.url cannot be empty");
+            bail!("{field}.url cannot be empty");
         }
         reqwest::Url::parse(&self.url)
-            .with_context(|| format!("webhook.url is not a valid URL: {}", self.url))?;
+            .with_context(|| format!("{field}.url is not a valid URL: {}", self.url))?;
         if self.timeout_seconds == 0 {
-            bail!("webhook.timeout_seconds must be greater than 0");
+            bail!("{field}.timeout_seconds must be greater than 0");
+        }
+        if self.events.is_empty() {
+            bail!("{field}.events cannot be empty");
+        }
+        if let Some(signing) = self.signing.as_ref() {
+            signing.validate(&format!("{field}.signing"))?;
+        }
+        if let Some(message) = self.message.as_ref() {
+            message.validate(&format!("{field}.message"))?;
         }
         Ok(())
     }
@@ -109,6 +483,59 @@ impl WebhookSettings {
     }
 }
 
+fn default_sink_events() -> Vec<EventKind> {
+    EventKind::ALL.to_vec()
+}
+
+/// HMAC-SHA256 request signing, see [`statushub::signing`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebhookSigningSettings {
+    #[serde(default)]
+    pub secret: Secret,
+    /// Reads `secret` from this file instead.
+    pub secret_file: Option<PathBuf>,
+    #[serde(default = "default_signature_header")]
+    pub signature_header: String,
+    #[serde(default = "default_timestamp_header")]
+    pub timestamp_header: String,
+}
+
+impl WebhookSigningSettings {
+    fn validate(&self, field: &str) -> Result<()> {
+        if self.secret.expose().trim().is_empty() {
+            bail!("{field}.secret (or {field}.secret_file) cannot be empty");
+        }
+        for (name, value) in [
+            ("signature_header", &self.signature_header),
+            ("timestamp_header", &self.timestamp_header),
+        ] {
+            reqwest::header::HeaderName::from_bytes(value.as_bytes())
+                .with_context(|| format!("{field}.{name} is not a valid header name: {value}"))?;
+        }
+        Ok(())
+    }
+
+    fn resolve_secret_file(&mut self, field: &str) -> Result<()> {
+        let mut secret =
+            Some(std::mem::take(&mut self.secret)).filter(|secret| !secret.expose().is_empty());
+        resolve_secret_file(
+            &format!("{field}.secret"),
+            &mut secret,
+            self.secret_file.take(),
+        )?;
+        self.secret = secret.unwrap_or_default();
+        Ok(())
+    }
+}
+
+fn default_signature_header() -> String {
+    signing::DEFAULT_SIGNATURE_HEADER.to_string()
+}
+
+fn default_timestamp_header() -> String {
+    signing::DEFAULT_TIMESTAMP_HEADER.to_string()
+}
+
 #[derive(Debug, Clone, Copy, Deserialize, Default)]
 #[serde(rename_all = "kebab-case")]
 pub enum OpenClawWakeMode {
@@ -155,13 +582,30 @@ fn default_emit_on_activity_change() -> bool {
 pub struct MessageTemplateSettings {
     pub prefix: Option<String>,
     pub suffix: Option<String>,
+    /// minijinja templates for the OpenClaw `text`, keyed by event kind. Kinds
+    /// without a template use the built-in text.
+    #[serde(default)]
+    pub templates: HashMap<EventKind, String>,
+}
+
+impl MessageTemplateSettings {
+    fn validate(&self, field: &str) -> Result<()> {
+        for (kind, source) in &self.templates {
+            minijinja::Environment::new()
+                .template_from_str(source)
+                .with_context(|| format!("{field}.templates.{kind} is not a valid template"))?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct SteamSettings {
     #[serde(default)]
     pub enabled: bool,
-    pub api_key: Option<String>,
+    pub api_key: Option<Secret>,
+    /// Reads `api_key` from this file instead.
+    pub api_key_file: Option<PathBuf>,
     #[serde(default = "default_steam_language")]
     pub language: String,
     #[serde(default = "default_steam_description_max_chars")]
@@ -181,6 +625,7 @@ impl Default for SteamSettings {
         Self {
             enabled: false,
             api_key: None,
+            api_key_file: None,
             language: default_steam_language(),
             description_max_chars: default_steam_description_max_chars(),
             timeout_seconds: default_steam_timeout_seconds(),
@@ -199,7 +644,7 @@ impl SteamSettings {
         if self
             .api_key
             .as_ref()
-            .is_some_and(|key| key.trim().is_empty())
+            .is_some_and(|key| key.expose().trim().is_empty())
         {
             bail!("steam.api_key cannot be empty when provided");
         }
@@ -256,6 +701,10 @@ pub struct ReminderSettings {
     pub steam_only: bool,
     #[serde(default = "default_reminder_check_interval_seconds")]
     pub check_interval_seconds: u64,
+    /// `[[reminder.rules]]`; when present they replace `interval_minutes` and
+    /// `steam_only`, and only states matching a rule are reminded of.
+    #[serde(default)]
+    pub rules: Vec<ReminderRuleSettings>,
 }
 
 impl Default for ReminderSettings {
@@ -265,6 +714,7 @@ impl Default for ReminderSettings {
             interval_minutes: default_reminder_interval_minutes(),
             steam_only: false,
             check_interval_seconds: default_reminder_check_interval_seconds(),
+            rules: Vec::new(),
         }
     }
 }
@@ -277,6 +727,9 @@ impl ReminderSettings {
         if self.check_interval_seconds == 0 {
             bail!("reminder.check_interval_seconds must be greater than 0");
         }
+        for (index, rule) in self.rules.iter().enumerate() {
+            rule.validate(&format!("reminder.rules[{index}]"))?;
+        }
         Ok(())
     }
 
@@ -285,6 +738,73 @@ impl ReminderSettings {
     }
 }
 
+/// One `[[reminder.rules]]` entry. The first rule whose conditions all match the
+/// current status and primary activity anchors the reminders.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReminderRuleSettings {
+    pub name: Option<String>,
+    #[serde(default)]
+    pub statuses: Vec<DiscordStatus>,
+    /// Regular expression matched against the primary activity name.
+    pub activity: Option<String>,
+    #[serde(default)]
+    pub steam_app_ids: Vec<u32>,
+    /// Gaps between reminders, e.g. `[30, 60, 120]` reminds after 30m, 90m and
+    /// then every 2h. Defaults to `[reminder].interval_minutes`.
+    #[serde(default)]
+    pub intervals_minutes: Vec<u64>,
+    /// Stop after this many reminders.
+    pub max_count: Option<u64>,
+    /// Stop reminding once the state has lasted this long.
+    pub max_duration_minutes: Option<u64>,
+    /// Send a `reminder_ended` event when the reminded state ends.
+    #[serde(default)]
+    pub emit_on_end: bool,
+}
+
+impl ReminderRuleSettings {
+    pub fn activity_regex(&self) -> Result<Option<Regex>> {
+        self.activity
+            .as_deref()
+            .map(|pattern| {
+                Regex::new(pattern).with_context(|| format!("invalid activity regex {pattern:?}"))
+            })
+            .transpose()
+    }
+
+    pub fn intervals_seconds(&self, default_minutes: u64) -> Vec<u64> {
+        if self.intervals_minutes.is_empty() {
+            return vec![default_minutes.saturating_mul(60)];
+        }
+        self.intervals_minutes
+            .iter()
+            .map(|minutes| minutes.saturating_mul(60))
+            .collect()
+    }
+
+    fn validate(&self, field: &str) -> Result<()> {
+        if self
+            .name
+            .as_deref()
+            .is_some_and(|name| name.trim().is_empty())
+        {
+            bail!("{field}.name cannot be empty when provided");
+        }
+        if self.intervals_minutes.contains(&0) {
+            bail!("{field}.intervals_minutes values must be greater than 0");
+        }
+        if self.max_count == Some(0) {
+            bail!("{field}.max_count must be greater than 0 when provided");
+        }
+        if self.max_duration_minutes == Some(0) {
+            bail!("{field}.max_duration_minutes must be greater than 0 when provided");
+        }
+        self.activity_regex()
+            .with_context(|| format!("invalid {field}.activity"))?;
+        Ok(())
+    }
+}
+
 fn default_reminder_interval_minutes() -> u64 {
     30
 }
@@ -365,82 +885,481 @@ fn default_state_cache_path() -> PathBuf {
     PathBuf::from("./data/status-state.json")
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn parse_valid_config() {
-        let raw = r#"
-            [discord]
-            bot_token = "discord-token"
-            user_id = 123456789
-            guild_id = 987654321
-            emit_initial_status = true
-            rich_presence_only = true
+#[derive(Debug, Clone, Deserialize)]
+pub struct OutboxSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_outbox_sqlite_path")]
+    pub sqlite_path: PathBuf,
+    #[serde(default = "default_outbox_max_attempts")]
+    pub max_attempts: u32,
+    #[serde(default = "default_outbox_initial_backoff_seconds")]
+    pub initial_backoff_seconds: u64,
+    #[serde(default = "default_outbox_max_backoff_seconds")]
+    pub max_backoff_seconds: u64,
+}
 
-            [webhook]
-            mode = "openclaw_wake"
-            url = "http://127.0.0.1:18789/hooks/wake"
-            token = "secret"
-            timeout_seconds = 10
+impl Default for OutboxSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sqlite_path: default_outbox_sqlite_path(),
+            max_attempts: default_outbox_max_attempts(),
+            initial_backoff_seconds: default_outbox_initial_backoff_seconds(),
+            max_backoff_seconds: default_outbox_max_backoff_seconds(),
+        }
+    }
+}
 
-            [webhook.openclaw]
-            wake_mode = "now"
+impl OutboxSettings {
+    fn validate(&self) -> Result<()> {
+        if self.enabled && self.sqlite_path.as_os_str().is_empty() {
+            bail!("outbox.sqlite_path cannot be empty when outbox.enabled=true");
+        }
+        if self.max_attempts == 0 {
+            bail!("outbox.max_attempts must be greater than 0");
+        }
+        if self.initial_backoff_seconds == 0 {
+            bail!("outbox.initial_backoff_seconds must be greater than 0");
+        }
+        if self.max_backoff_seconds < self.initial_backoff_seconds {
+            bail!("outbox.max_backoff_seconds must be >= outbox.initial_backoff_seconds");
+        }
+        Ok(())
+    }
+}
 
-            [message]
-            prefix = "[PREFIX]"
-            suffix = "[SUFFIX]"
+fn default_outbox_sqlite_path() -> PathBuf {
+    PathBuf::from("./data/statushub-outbox.sqlite3")
+}
 
-            [steam]
-            enabled = true
-            api_key = "steam-api-key"
-            language = "schinese"
-            description_max_chars = 200
-            timeout_seconds = 5
-            memory_cache_ttl_seconds = 60
-            memory_cache_capacity = 128
-            db_cache_ttl_seconds = 3600
+fn default_outbox_max_attempts() -> u32 {
+    10
+}
 
-            [reminder]
-            enabled = true
-            interval_minutes = 30
-            steam_only = true
-            check_interval_seconds = 30
+fn default_outbox_initial_backoff_seconds() -> u64 {
+    5
+}
 
-            [cache]
-            backend = "sqlite"
-            sqlite_path = "./tmp/cache.sqlite3"
+fn default_outbox_max_backoff_seconds() -> u64 {
+    600
+}
 
-            [state_cache]
-            enabled = true
-            path = "./tmp/state.json"
-        "#;
+#[derive(Debug, Clone, Deserialize)]
+pub struct HistorySettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_history_sqlite_path")]
+    pub sqlite_path: PathBuf,
+}
 
-        let settings: Settings = toml::from_str(raw).expect("config should parse");
-        settings.validate().expect("config should validate");
-        assert_eq!(settings.discord.user_id, 123456789);
-        assert!(settings.discord.emit_initial_status);
-        assert!(settings.discord.emit_on_activity_change);
-        assert!(settings.discord.rich_presence_only);
-        assert_eq!(settings.message.prefix.as_deref(), Some("[PREFIX]"));
-        assert!(settings.steam.enabled);
-        assert!(settings.reminder.enabled);
-        assert!(settings.reminder.steam_only);
-        assert!(matches!(settings.cache.backend, CacheBackend::Sqlite));
-        assert!(settings.state_cache.enabled);
+impl Default for HistorySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sqlite_path: default_history_sqlite_path(),
+        }
     }
+}
 
-    #[test]
-    fn reject_invalid_webhook_url() {
-        let raw = r#"
-            [discord]
-            bot_token = "discord-token"
-            user_id = 123456789
+impl HistorySettings {
+    fn validate(&self) -> Result<()> {
+        if self.enabled && self.sqlite_path.as_os_str().is_empty() {
+            bail!("history.sqlite_path cannot be empty when history.enabled=true");
+        }
+        Ok(())
+    }
+}
 
-            [webhook]
-            url = "not-a-url"
-        "#;
+fn default_history_sqlite_path() -> PathBuf {
+    PathBuf::from("./data/statushub-history.sqlite3")
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DigestSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Local time of day (`HH:MM`) at which digests are emitted.
+    #[serde(default = "default_digest_time")]
+    pub time: String,
+    #[serde(default = "default_digest_daily")]
+    pub daily: bool,
+    #[serde(default)]
+    pub weekly: bool,
+    #[serde(default = "default_digest_weekly_day")]
+    pub weekly_day: Weekday,
+    #[serde(default = "default_digest_top_n")]
+    pub top_n: usize,
+}
+
+impl Default for DigestSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            time: default_digest_time(),
+            daily: default_digest_daily(),
+            weekly: false,
+            weekly_day: default_digest_weekly_day(),
+            top_n: default_digest_top_n(),
+        }
+    }
+}
+
+impl DigestSettings {
+    pub fn local_time(&self) -> Result<NaiveTime> {
+        NaiveTime::parse_from_str(self.time.trim(), "%H:%M")
+            .with_context(|| format!("digest.time must be HH:MM, got {:?}", self.time))
+    }
+
+    fn validate(&self) -> Result<()> {
+        self.local_time()?;
+        if self.enabled && !self.daily && !self.weekly {
+            bail!("digest.daily or digest.weekly must be true when digest.enabled=true");
+        }
+        if self.top_n == 0 {
+            bail!("digest.top_n must be greater than 0");
+        }
+        Ok(())
+    }
+}
+
+fn default_digest_time() -> String {
+    "21:00".to_string()
+}
+
+fn default_digest_daily() -> bool {
+    true
+}
+
+fn default_digest_weekly_day() -> Weekday {
+    Weekday::Sun
+}
+
+fn default_digest_top_n() -> usize {
+    5
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleAction {
+    /// Deliver to every sink that accepts the event kind and stop evaluating rules.
+    Allow,
+    /// Deliver to no sink.
+    Drop,
+    /// Deliver only to the sinks listed in `sinks`.
+    Route,
+}
+
+/// One `[[rules]]` entry. Every condition that is set must match; the first
+/// matching rule decides what happens to an event.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RuleSettings {
+    pub name: Option<String>,
+    pub action: RuleAction,
+    /// Target sinks of a `route` rule.
+    #[serde(default)]
+    pub sinks: Vec<String>,
+    #[serde(default)]
+    pub kinds: Vec<EventKind>,
+    #[serde(default)]
+    pub user_ids: Vec<u64>,
+    /// Matches the status before the change; events without one never match.
+    #[serde(default)]
+    pub from_status: Vec<DiscordStatus>,
+    #[serde(default)]
+    pub to_status: Vec<DiscordStatus>,
+    /// Regular expression matched against the name of any current activity.
+    pub activity: Option<String>,
+    /// Matches when any current activity is one of these Steam games.
+    #[serde(default)]
+    pub steam_app_ids: Vec<u32>,
+    /// Time of day range `HH:MM-HH:MM` in `timezone`; may wrap past midnight.
+    pub time: Option<String>,
+    /// IANA time zone such as `Asia/Shanghai` for `time`; the system time zone
+    /// when unset.
+    pub timezone: Option<String>,
+}
+
+impl RuleSettings {
+    pub fn activity_regex(&self) -> Result<Option<Regex>> {
+        self.activity
+            .as_deref()
+            .map(|pattern| {
+                Regex::new(pattern).with_context(|| format!("invalid activity regex {pattern:?}"))
+            })
+            .transpose()
+    }
+
+    pub fn time_range(&self) -> Result<Option<(NaiveTime, NaiveTime)>> {
+        self.time.as_deref().map(parse_time_range).transpose()
+    }
+
+    pub fn tz(&self) -> Result<Option<Tz>> {
+        self.timezone
+            .as_deref()
+            .map(|name| {
+                name.trim()
+                    .parse::<Tz>()
+                    .ok()
+                    .with_context(|| format!("not a known IANA time zone: {name:?}"))
+            })
+            .transpose()
+    }
+
+    fn validate(&self, field: &str, sink_names: &HashSet<String>) -> Result<()> {
+        if self
+            .name
+            .as_deref()
+            .is_some_and(|name| name.trim().is_empty())
+        {
+            bail!("{field}.name cannot be empty when provided");
+        }
+        match self.action {
+            RuleAction::Route if self.sinks.is_empty() => {
+                bail!("{field}.sinks cannot be empty when {field}.action=route");
+            }
+            RuleAction::Allow | RuleAction::Drop if !self.sinks.is_empty() => {
+                bail!("{field}.sinks is only allowed when {field}.action=route");
+            }
+            _ => {}
+        }
+        if let Some(sink) = self.sinks.iter().find(|sink| !sink_names.contains(*sink)) {
+            bail!("{field}.sinks references unknown sink {sink}");
+        }
+        self.activity_regex()
+            .with_context(|| format!("invalid {field}.activity"))?;
+        self.time_range()
+            .with_context(|| format!("invalid {field}.time"))?;
+        self.tz()
+            .with_context(|| format!("invalid {field}.timezone"))?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuietHoursAction {
+    /// Discard events produced during quiet hours.
+    Drop,
+    /// Hold events until the window ends, in the outbox when one is configured and
+    /// in memory otherwise.
+    #[default]
+    Defer,
+    /// Deliver right away, but OpenClaw sinks wake with `next-heartbeat`.
+    NextHeartbeat,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct QuietHoursSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// IANA time zone such as `Asia/Shanghai`; the system time zone when unset.
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub action: QuietHoursAction,
+    /// Event kinds held to quiet hours; other kinds are delivered as usual.
+    #[serde(default = "default_sink_events")]
+    pub kinds: Vec<EventKind>,
+    #[serde(default)]
+    pub windows: Vec<QuietWindowSettings>,
+}
+
+impl Default for QuietHoursSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            timezone: None,
+            action: QuietHoursAction::default(),
+            kinds: default_sink_events(),
+            windows: Vec::new(),
+        }
+    }
+}
+
+impl QuietHoursSettings {
+    pub fn tz(&self) -> Result<Option<Tz>> {
+        self.timezone
+            .as_deref()
+            .map(|name| {
+                name.trim().parse::<Tz>().ok().with_context(|| {
+                    format!("quiet_hours.timezone is not a known IANA time zone: {name:?}")
+                })
+            })
+            .transpose()
+    }
+
+    fn validate(&self) -> Result<()> {
+        self.tz()?;
+        if self.enabled && self.windows.is_empty() {
+            bail!("quiet_hours.windows cannot be empty when quiet_hours.enabled=true");
+        }
+        if self.kinds.is_empty() {
+            bail!("quiet_hours.kinds cannot be empty");
+        }
+        for (index, window) in self.windows.iter().enumerate() {
+            let field = format!("quiet_hours.windows[{index}]");
+            if window.days.is_empty() {
+                bail!("{field}.days cannot be empty");
+            }
+            window
+                .time_range()
+                .with_context(|| format!("invalid {field}.time"))?;
+        }
+        Ok(())
+    }
+}
+
+/// A quiet window on the listed weekdays. A window that wraps past midnight
+/// belongs to the day it starts on.
+#[derive(Debug, Clone, Deserialize)]
+pub struct QuietWindowSettings {
+    #[serde(default = "default_quiet_window_days")]
+    pub days: Vec<Weekday>,
+    /// Time of day range `HH:MM-HH:MM` in the quiet hours time zone.
+    pub time: String,
+}
+
+impl QuietWindowSettings {
+    pub fn time_range(&self) -> Result<(NaiveTime, NaiveTime)> {
+        parse_time_range(&self.time)
+    }
+}
+
+fn default_quiet_window_days() -> Vec<Weekday> {
+    vec![
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+        Weekday::Sat,
+        Weekday::Sun,
+    ]
+}
+
+/// Parses `HH:MM-HH:MM`.
+fn parse_time_range(raw: &str) -> Result<(NaiveTime, NaiveTime)> {
+    let (start, end) = raw
+        .split_once('-')
+        .with_context(|| format!("time range must be HH:MM-HH:MM, got {raw:?}"))?;
+    let parse = |value: &str| {
+        NaiveTime::parse_from_str(value.trim(), "%H:%M")
+            .with_context(|| format!("time range must be HH:MM-HH:MM, got {raw:?}"))
+    };
+    let range = (parse(start)?, parse(end)?);
+    if range.0 == range.1 {
+        bail!("time range start and end cannot be equal, got {raw:?}");
+    }
+    Ok(range)
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HttpSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_http_bind")]
+    pub bind: SocketAddr,
+}
+
+impl Default for HttpSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: default_http_bind(),
+        }
+    }
+}
+
+fn default_http_bind() -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], 8080))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_valid_config() {
+        let raw = r#"
+            [discord]
+            bot_token = "discord-token"
+            user_id = 123456789
+            guild_id = 987654321
+            emit_initial_status = true
+            rich_presence_only = true
+
+            [webhook]
+            mode = "openclaw_wake"
+            url = "http://127.0.0.1:18789/hooks/wake"
+            token = "secret"
+            timeout_seconds = 10
+
+            [webhook.openclaw]
+            wake_mode = "now"
+
+            [message]
+            prefix = "[PREFIX]"
+            suffix = "[SUFFIX]"
+
+            [steam]
+            enabled = true
+            api_key = "steam-api-key"
+            language = "schinese"
+            description_max_chars = 200
+            timeout_seconds = 5
+            memory_cache_ttl_seconds = 60
+            memory_cache_capacity = 128
+            db_cache_ttl_seconds = 3600
+
+            [reminder]
+            enabled = true
+            interval_minutes = 30
+            steam_only = true
+            check_interval_seconds = 30
+
+            [cache]
+            backend = "sqlite"
+            sqlite_path = "./tmp/cache.sqlite3"
+
+            [state_cache]
+            enabled = true
+            path = "./tmp/state.json"
+
+            [http]
+            enabled = true
+            bind = "0.0.0.0:9090"
+        "#;
+
+        let settings: Settings = toml::from_str(raw).expect("config should parse");
+        settings.validate().expect("config should validate");
+        assert_eq!(settings.discord.user_id, Some(123456789));
+        assert!(settings.discord.emit_initial_status);
+        assert!(settings.discord.emit_on_activity_change);
+        assert!(settings.discord.rich_presence_only);
+        assert_eq!(settings.message.prefix.as_deref(), Some("[PREFIX]"));
+        assert!(settings.steam.enabled);
+        assert!(settings.reminder.enabled);
+        assert!(settings.reminder.steam_only);
+        assert!(matches!(settings.cache.backend, CacheBackend::Sqlite));
+        assert!(settings.state_cache.enabled);
+        assert!(settings.http.enabled);
+        assert_eq!(settings.http.bind.port(), 9090);
+        let sinks = settings.webhook.sinks();
+        assert_eq!(sinks.len(), 1);
+        assert_eq!(sinks[0].name, DEFAULT_SINK_NAME);
+        assert_eq!(sinks[0].events, EventKind::ALL.to_vec());
+    }
+
+    #[test]
+    fn reject_invalid_webhook_url() {
+        let raw = r#"
+            [discord]
+            bot_token = "discord-token"
+            user_id = 123456789
+
+            [webhook]
+            url = "not-a-url"
+        "#;
 
         let settings: Settings = toml::from_str(raw).expect("config should parse");
         let err = settings.validate().expect_err("config should fail");
@@ -464,4 +1383,418 @@ mod tests {
         let err = settings.validate().expect_err("config should fail");
         assert!(err.to_string().contains("emit_on_activity_change"));
     }
+
+    #[test]
+    fn parse_multiple_targets() {
+        let raw = r#"
+            [discord]
+            bot_token = "discord-token"
+
+            [[discord.targets]]
+            user_id = 1
+            guild_id = 10
+            emit_on_platform_change = true
+            emit_on_custom_status_change = true
+            emit_on_track_change = true
+            track_change_min_interval_seconds = 60
+            status_debounce_seconds = 30
+
+            [[discord.targets]]
+            user_id = 2
+            rich_presence_only = true
+
+            [discord.targets.reminder]
+            enabled = true
+            interval_minutes = 15
+
+            [webhook]
+            url = "http://127.0.0.1:18789/hooks/wake"
+        "#;
+
+        let settings: Settings = toml::from_str(raw).expect("config should parse");
+        settings.validate().expect("config should validate");
+        let targets = settings.discord.targets();
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[0].guild_id, Some(10));
+        assert!(targets[0].reminder.is_none());
+        assert!(targets[0].emit_on_platform_change);
+        assert!(targets[0].emit_on_custom_status_change);
+        assert!(targets[0].emit_on_track_change);
+        assert_eq!(targets[0].track_change_min_interval_seconds, 60);
+        assert_eq!(targets[0].status_debounce_seconds, 30);
+        assert_eq!(targets[1].activity_debounce_seconds, 0);
+        assert!(targets[1].rich_presence_only);
+        assert!(!targets[1].emit_on_platform_change);
+        assert_eq!(
+            targets[1].reminder.as_ref().map(|r| r.interval_minutes),
+            Some(15)
+        );
+    }
+
+    #[test]
+    fn reject_rule_routing_to_unknown_sink() {
+        let raw = r#"
+            [discord]
+            bot_token = "discord-token"
+            user_id = 1
+
+            [webhook]
+            url = "http://127.0.0.1:18789/hooks/wake"
+
+            [[rules]]
+            action = "drop"
+            activity = "^Visual Studio Code$"
+            time = "22:00-07:00"
+
+            [[rules]]
+            action = "route"
+            sinks = ["games"]
+        "#;
+
+        let settings: Settings = toml::from_str(raw).expect("config should parse");
+        let err = settings.validate().expect_err("config should fail");
+        assert!(err.to_string().contains("rules[1].sinks"));
+    }
+
+    #[test]
+    fn reject_reminder_rule_with_zero_interval() {
+        let raw = r#"
+            [discord]
+            bot_token = "discord-token"
+            user_id = 1
+
+            [webhook]
+            url = "http://127.0.0.1:18789/hooks/wake"
+
+            [reminder]
+            enabled = true
+
+            [[reminder.rules]]
+            steam_app_ids = [570]
+            intervals_minutes = [30, 0]
+        "#;
+
+        let settings: Settings = toml::from_str(raw).expect("config should parse");
+        let err = settings.validate().expect_err("config should fail");
+        assert!(
+            err.to_string()
+                .contains("reminder.rules[0].intervals_minutes")
+        );
+    }
+
+    #[test]
+    fn parse_quiet_hours() {
+        let raw = r#"
+            [discord]
+            bot_token = "discord-token"
+            user_id = 1
+
+            [webhook]
+            url = "http://127.0.0.1:18789/hooks/wake"
+
+            [quiet_hours]
+            enabled = true
+            timezone = "Asia/Shanghai"
+            action = "next_heartbeat"
+
+            [[quiet_hours.windows]]
+            time = "23:00-07:00"
+
+            [[quiet_hours.windows]]
+            days = ["sat", "sun"]
+            time = "13:00-14:00"
+        "#;
+
+        let settings: Settings = toml::from_str(raw).expect("config should parse");
+        settings.validate().expect("config should validate");
+        let quiet_hours = &settings.quiet_hours;
+        assert_eq!(quiet_hours.action, QuietHoursAction::NextHeartbeat);
+        assert_eq!(
+            quiet_hours.tz().expect("timezone should parse"),
+            Some(chrono_tz::Asia::Shanghai)
+        );
+        assert_eq!(quiet_hours.windows[0].days.len(), 7);
+        assert_eq!(
+            quiet_hours.windows[1].days,
+            vec![Weekday::Sat, Weekday::Sun]
+        );
+    }
+
+    #[test]
+    fn reject_duplicate_targets() {
+        let raw = r#"
+            [discord]
+            bot_token = "discord-token"
+
+            [[discord.targets]]
+            user_id = 1
+
+            [[discord.targets]]
+            user_id = 1
+
+            [webhook]
+            url = "http://127.0.0.1:18789/hooks/wake"
+        "#;
+
+        let settings: Settings = toml::from_str(raw).expect("config should parse");
+        let err = settings.validate().expect_err("config should fail");
+        assert!(err.to_string().contains("discord.targets[1]"));
+    }
+
+    #[test]
+    fn parse_webhook_sinks() {
+        let raw = r#"
+            [discord]
+            bot_token = "discord-token"
+            user_id = 1
+
+            [[webhook.sinks]]
+            name = "openclaw"
+            url = "http://127.0.0.1:18789/hooks/wake"
+            token = "secret"
+            events = ["status_change", "reminder"]
+
+            [webhook.sinks.message]
+            prefix = "[OC]"
+
+            [[webhook.sinks]]
+            name = "log"
+            mode = "generic_json"
+            url = "http://127.0.0.1:9000/log"
+            timeout_seconds = 3
+        "#;
+
+        let settings: Settings = toml::from_str(raw).expect("config should parse");
+        settings.validate().expect("config should validate");
+        let sinks = settings.webhook.sinks();
+        assert_eq!(sinks.len(), 2);
+        assert_eq!(
+            sinks[0].events,
+            vec![EventKind::StatusChange, EventKind::Reminder]
+        );
+        assert_eq!(
+            sinks[0].message.as_ref().and_then(|m| m.prefix.as_deref()),
+            Some("[OC]")
+        );
+        assert!(matches!(sinks[1].mode, WebhookMode::GenericJson));
+        assert_eq!(sinks[1].events, EventKind::ALL.to_vec());
+        assert!(sinks[1].signing.is_none());
+    }
+
+    #[test]
+    fn parse_webhook_signing_defaults() {
+        let raw = r#"
+            [discord]
+            bot_token = "discord-token"
+            user_id = 1
+
+            [webhook]
+            url = "http://127.0.0.1:18789/hooks/wake"
+
+            [webhook.signing]
+            secret = "shared-secret"
+        "#;
+
+        let settings: Settings = toml::from_str(raw).expect("config should parse");
+        settings.validate().expect("config should validate");
+        let signing = settings.webhook.sinks()[0]
+            .signing
+            .clone()
+            .expect("signing should be set");
+        assert_eq!(signing.signature_header, "x-statushub-signature");
+        assert_eq!(signing.timestamp_header, "x-statushub-timestamp");
+    }
+
+    #[test]
+    fn parse_message_templates() {
+        let raw = r#"
+            [discord]
+            bot_token = "discord-token"
+            user_id = 1
+
+            [webhook]
+            url = "http://127.0.0.1:18789/hooks/wake"
+
+            [message.templates]
+            status_change = "{{ user_id }}: {{ previous_status }} -> {{ current_status }}"
+            reminder = "still {{ current_status }} after {{ reminder.elapsed_seconds | duration }}"
+        "#;
+
+        let settings: Settings = toml::from_str(raw).expect("config should parse");
+        settings.validate().expect("config should validate");
+        assert_eq!(settings.message.templates.len(), 2);
+        assert!(
+            settings
+                .message
+                .templates
+                .contains_key(&EventKind::StatusChange)
+        );
+    }
+
+    #[test]
+    fn reject_invalid_message_template() {
+        let raw = r#"
+            [discord]
+            bot_token = "discord-token"
+            user_id = 1
+
+            [webhook]
+            url = "http://127.0.0.1:18789/hooks/wake"
+
+            [message.templates]
+            activity_change = "{% if %}"
+        "#;
+
+        let settings: Settings = toml::from_str(raw).expect("config should parse");
+        let err = settings.validate().expect_err("config should fail");
+        assert!(
+            err.to_string()
+                .contains("message.templates.activity_change")
+        );
+    }
+
+    #[test]
+    fn reject_duplicate_sink_names() {
+        let raw = r#"
+            [discord]
+            bot_token = "discord-token"
+            user_id = 1
+
+            [[webhook.sinks]]
+            name = "a"
+            url = "http://127.0.0.1:9000/a"
+
+            [[webhook.sinks]]
+            name = "a"
+            url = "http://127.0.0.1:9000/b"
+        "#;
+
+        let settings: Settings = toml::from_str(raw).expect("config should parse");
+        let err = settings.validate().expect_err("config should fail");
+        assert!(err.to_string().contains("webhook.sinks[1].name"));
+    }
+
+    #[test]
+    fn parse_digest_settings() {
+        let raw = r#"
+            [discord]
+            bot_token = "discord-token"
+            user_id = 1
+
+            [webhook]
+            url = "http://127.0.0.1:9000/hook"
+
+            [history]
+            enabled = true
+
+            [digest]
+            enabled = true
+            time = "21:30"
+            weekly = true
+            weekly_day = "sat"
+        "#;
+
+        let settings: Settings = toml::from_str(raw).expect("config should parse");
+        settings.validate().expect("config should be valid");
+        assert_eq!(
+            settings.digest.local_time().expect("time should parse"),
+            NaiveTime::from_hms_opt(21, 30, 0).expect("valid time")
+        );
+        assert!(settings.digest.daily);
+        assert_eq!(settings.digest.weekly_day, Weekday::Sat);
+    }
+
+    #[test]
+    fn reject_digest_without_history() {
+        let raw = r#"
+            [discord]
+            bot_token = "discord-token"
+            user_id = 1
+
+            [webhook]
+            url = "http://127.0.0.1:9000/hook"
+
+            [digest]
+            enabled = true
+        "#;
+
+        let settings: Settings = toml::from_str(raw).expect("config should parse");
+        let err = settings.validate().expect_err("config should fail");
+        assert!(err.to_string().contains("history.enabled"));
+    }
+
+    #[test]
+    fn expand_env_references() {
+        let lookup = |name: &str| (name == "TOKEN").then(|| "s3cret".to_string());
+        assert_eq!(
+            expand_env("Bearer ${TOKEN}, $5 and $${TOKEN}", &lookup).expect("should expand"),
+            "Bearer s3cret, $5 and ${TOKEN}"
+        );
+        let err = expand_env("${MISSING}", &lookup).expect_err("should fail");
+        assert!(err.to_string().contains("MISSING is not set"));
+        assert!(expand_env("${TOKEN", &lookup).is_err());
+
+        let mut value: toml::Value = toml::from_str(
+            r#"
+            [webhook]
+            url = "http://${HOST}/hook"
+            "#,
+        )
+        .expect("toml should parse");
+        let err = interpolate_env(&mut value, "", &lookup).expect_err("should fail");
+        assert_eq!(err.to_string(), "invalid webhook.url");
+    }
+
+    #[test]
+    fn load_secrets_from_files_and_redact_debug() {
+        let dir = std::env::temp_dir().join(format!(
+            "statushub_secrets_{}",
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        fs::create_dir_all(&dir).expect("dir should be created");
+        fs::write(dir.join("discord"), "discord-secret-token\n").expect("secret written");
+        fs::write(dir.join("hook"), "hook-secret-token").expect("secret written");
+        let config = dir.join("config.toml");
+        let raw = format!(
+            r#"
+            [discord]
+            bot_token_file = "{dir}/discord"
+            user_id = 1
+
+            [webhook]
+            url = "http://127.0.0.1:18789/hooks/wake"
+            token_file = "{dir}/hook"
+
+            [webhook.signing]
+            secret = "signing-secret-value"
+            "#,
+            dir = dir.display()
+        );
+        fs::write(&config, &raw).expect("config written");
+
+        let settings = Settings::load_from_path(&config).expect("config should load");
+        assert_eq!(settings.discord.bot_token.expose(), "discord-secret-token");
+        let sinks = settings.webhook.sinks();
+        assert_eq!(
+            sinks[0].token.as_ref().map(Secret::expose),
+            Some("hook-secret-token")
+        );
+        let debug = format!("{settings:?}");
+        assert!(!debug.contains("secret-token"));
+        assert!(!debug.contains("signing-secret-value"));
+        assert!(debug.contains("<redacted>"));
+
+        let both = raw.replace(
+            "\n            token_file",
+            "\ntoken = \"inline\"\ntoken_file",
+        );
+        fs::write(&config, both).expect("config written");
+        let err = Settings::load_from_path(&config).expect_err("config should fail");
+        assert!(
+            err.to_string()
+                .contains("webhook.token and webhook.token_file cannot be used together")
+        );
+
+        let _ = fs::remove_dir_all(dir);
+    }
 }
@@ -0,0 +1,504 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
+
+use crate::cache::CacheService;
+use crate::config::Settings;
+use crate::event::DiscordStatusChangedEvent;
+use crate::hub::EventHub;
+use crate::metrics::metrics;
+use crate::outbox::{FailureOutcome, Outbox, OutboxEntry, now_unix_seconds};
+use crate::quiet_hours::{QuietDecision, QuietHours};
+use crate::rules::RuleSet;
+use crate::webhook::{self, WebhookSender, WebhookSink};
+
+const OUTBOX_ERROR_RETRY_DELAY: Duration = Duration::from_secs(5);
+const SINK_QUEUE_CAPACITY: usize = 256;
+
+/// The reloadable part of delivery: sinks with their senders and message
+/// templates, `[[rules]]` and quiet hours.
+pub struct Pipeline {
+    pub sinks: Vec<WebhookSink>,
+    pub rules: RuleSet,
+    pub quiet_hours: Option<Arc<QuietHours>>,
+    /// Sinks log rendered requests instead of sending them; kept across reloads.
+    pub dry_run: bool,
+}
+
+impl Pipeline {
+    pub fn from_settings(
+        settings: &Settings,
+        cache_service: Arc<CacheService>,
+        dry_run: bool,
+    ) -> Result<Self> {
+        Ok(Self {
+            sinks: webhook::build_sinks(
+                &settings.webhook,
+                &settings.message,
+                &settings.steam,
+                cache_service,
+                dry_run,
+            )
+            .context("failed to setup webhook sinks")?,
+            rules: RuleSet::new(&settings.rules).context("failed to compile rules")?,
+            quiet_hours: QuietHours::new(&settings.quiet_hours)
+                .context("failed to setup quiet hours")?
+                .map(Arc::new),
+            dry_run,
+        })
+    }
+}
+
+/// Consumes produced events, publishes them to live subscribers and fans them out
+/// to every sink that accepts the event kind and is not excluded by `[[rules]]`.
+///
+/// Each sink is served by its own worker, so a slow or failing sink does not delay
+/// the others. Without an outbox every event is sent at most once: a sink keeps up
+/// to `SINK_QUEUE_CAPACITY` events in memory and drops further ones while its queue
+/// is full. With an outbox, events are persisted for every sink before its worker
+/// is woken and delivered in order with retries, so they survive slow sinks,
+/// webhook outages and process restarts.
+///
+/// During quiet hours events are dropped, held until the window ends, or flagged
+/// for a `next-heartbeat` wake, depending on `[quiet_hours].action`. Held events
+/// wait in the outbox when there is one and in memory otherwise.
+///
+/// A new pipeline sent on `pipeline` takes effect for the next event: workers of
+/// sinks that are still configured keep their queue and switch to the new sender.
+pub async fn run(
+    mut rx: mpsc::Receiver<DiscordStatusChangedEvent>,
+    mut pipeline: watch::Receiver<Arc<Pipeline>>,
+    outbox: Option<Arc<Outbox>>,
+    hub: EventHub,
+) {
+    let initial = pipeline.borrow_and_update().clone();
+    let mut router = Router::new(initial, outbox);
+    let mut deferred: Vec<(DateTime<Utc>, DiscordStatusChangedEvent)> = Vec::new();
+
+    loop {
+        let next_release = deferred.iter().map(|(release_at, _)| *release_at).min();
+        let event = tokio::select! {
+            maybe_event = rx.recv() => match maybe_event {
+                Some(event) => {
+                    hub.publish(&event);
+                    metrics()
+                        .events_emitted
+                        .with_label_values(&[event.kind().to_string().as_str()])
+                        .inc();
+                    event
+                }
+                None => break,
+            },
+            _ = sleep_until(next_release.unwrap_or_default()), if next_release.is_some() => {
+                let now = Utc::now();
+                let (due, pending) = deferred
+                    .into_iter()
+                    .partition(|(release_at, _)| *release_at <= now);
+                deferred = pending;
+                for (_, event) in due {
+                    // Back-to-back windows keep the event deferred.
+                    dispatch(&router, &mut deferred, event).await;
+                }
+                continue;
+            }
+            Ok(()) = pipeline.changed() => {
+                let next = pipeline.borrow_and_update().clone();
+                router.apply(next);
+                continue;
+            }
+        };
+        dispatch(&router, &mut deferred, event).await;
+    }
+
+    if !deferred.is_empty() {
+        warn!(
+            count = deferred.len(),
+            "dropping events deferred by quiet hours on shutdown"
+        );
+    }
+    router.shutdown().await;
+}
+
+struct Router {
+    pipeline: Arc<Pipeline>,
+    routes: Vec<Route>,
+    /// Workers of removed sinks, finishing what was already queued for them.
+    retired: Vec<(String, JoinHandle<()>)>,
+    outbox: Option<Arc<Outbox>>,
+}
+
+struct Route {
+    sink: WebhookSink,
+    queue: RouteQueue,
+    sender: watch::Sender<Arc<dyn WebhookSender>>,
+    worker: JoinHandle<()>,
+}
+
+/// How events reach a sink worker. Dropping it lets the worker finish.
+enum RouteQueue {
+    /// Events are handed over in memory.
+    Direct(mpsc::Sender<DiscordStatusChangedEvent>),
+    /// Events are written to the outbox by the router; the worker is only woken.
+    Outbox {
+        outbox: Arc<Outbox>,
+        wake: mpsc::Sender<()>,
+    },
+}
+
+impl Router {
+    fn new(pipeline: Arc<Pipeline>, outbox: Option<Arc<Outbox>>) -> Self {
+        let mut router = Self {
+            pipeline: pipeline.clone(),
+            routes: Vec::new(),
+            retired: Vec::new(),
+            outbox,
+        };
+        router.apply(pipeline);
+        router
+    }
+
+    fn apply(&mut self, pipeline: Arc<Pipeline>) {
+        let mut previous = std::mem::take(&mut self.routes);
+        for sink in &pipeline.sinks {
+            let route = match previous
+                .iter()
+                .position(|route| route.sink.name == sink.name)
+            {
+                Some(index) => {
+                    let mut route = previous.swap_remove(index);
+                    route.sender.send_replace(sink.sender.clone());
+                    route.sink = sink.clone();
+                    route
+                }
+                None => self.spawn(sink),
+            };
+            self.routes.push(route);
+        }
+        for route in previous {
+            info!(sink = %route.sink.name, "webhook sink removed");
+            // Dropping the queue lets the worker finish what it already has.
+            self.retired.push((route.sink.name, route.worker));
+        }
+        self.retired.retain(|(_, worker)| !worker.is_finished());
+        self.pipeline = pipeline;
+    }
+
+    fn spawn(&mut self, sink: &WebhookSink) -> Route {
+        // A sink added back must not race its old worker over the same outbox queue.
+        for (_, worker) in self.retired.iter().filter(|(name, _)| *name == sink.name) {
+            worker.abort();
+        }
+        let (sender, sender_rx) = watch::channel(sink.sender.clone());
+        let name = sink.name.clone();
+        let (queue, worker) = match self.outbox.clone() {
+            Some(outbox) => {
+                // A pending wake-up covers any number of new events.
+                let (tx, rx) = mpsc::channel(1);
+                let worker = tokio::spawn(run_with_outbox(name, rx, sender_rx, outbox.clone()));
+                (RouteQueue::Outbox { outbox, wake: tx }, worker)
+            }
+            None => {
+                let (tx, rx) = mpsc::channel(SINK_QUEUE_CAPACITY);
+                let worker = tokio::spawn(run_direct(name, rx, sender_rx, self.pipeline.dry_run));
+                (RouteQueue::Direct(tx), worker)
+            }
+        };
+        info!(sink = %sink.name, events = ?sink.events, "webhook sink ready");
+        Route {
+            sink: sink.clone(),
+            queue,
+            sender,
+            worker,
+        }
+    }
+
+    /// Hands the event to every sink it is for. `deliver_at` is only honoured with
+    /// an outbox; without one, deferred events never get here.
+    async fn route(&self, event: DiscordStatusChangedEvent, deliver_at: Option<DateTime<Utc>>) {
+        let kind = event.kind();
+        let rule = self.pipeline.rules.evaluate(&event);
+        if let Some(rule) = rule {
+            metrics()
+                .rule_matches
+                .with_label_values(&[rule.rule, rule.action_label()])
+                .inc();
+            debug!(
+                rule = rule.rule,
+                action = rule.action_label(),
+                kind = %kind,
+                user_id = event.user_id,
+                "event matched rule"
+            );
+        }
+        for route in &self.routes {
+            if !route.sink.accepts(kind)
+                || rule.is_some_and(|rule| !rule.delivers_to(&route.sink.name))
+            {
+                continue;
+            }
+            let tx = match &route.queue {
+                RouteQueue::Outbox { outbox, wake } => {
+                    enqueue(outbox, &route.sink.name, &event, deliver_at).await;
+                    if let Err(TrySendError::Closed(())) = wake.try_send(()) {
+                        warn!(sink = %route.sink.name, "webhook sink worker stopped, event kept in outbox");
+                    }
+                    continue;
+                }
+                RouteQueue::Direct(tx) => tx,
+            };
+            match tx.try_send(event.clone()) {
+                Ok(()) => {}
+                Err(TrySendError::Full(_)) => {
+                    metrics()
+                        .sink_queue_drops
+                        .with_label_values(&[route.sink.name.as_str()])
+                        .inc();
+                    warn!(
+                        sink = %route.sink.name,
+                        kind = %kind,
+                        user_id = event.user_id,
+                        "webhook sink queue is full, event dropped"
+                    );
+                }
+                Err(TrySendError::Closed(_)) => {
+                    warn!(sink = %route.sink.name, "webhook sink worker stopped, event dropped");
+                }
+            }
+        }
+    }
+
+    async fn shutdown(self) {
+        let workers = self
+            .routes
+            .into_iter()
+            .map(|route| route.worker)
+            .chain(self.retired.into_iter().map(|(_, worker)| worker));
+        for worker in workers {
+            let _ = worker.await;
+        }
+    }
+}
+
+async fn dispatch(
+    router: &Router,
+    deferred: &mut Vec<(DateTime<Utc>, DiscordStatusChangedEvent)>,
+    mut event: DiscordStatusChangedEvent,
+) {
+    let decision = router
+        .pipeline
+        .quiet_hours
+        .as_ref()
+        .map_or(QuietDecision::Deliver, |quiet_hours| {
+            quiet_hours.decide(&event, Utc::now())
+        });
+    match decision {
+        QuietDecision::Deliver => {}
+        QuietDecision::Drop => {
+            info!(kind = %event.kind(), user_id = event.user_id, "event dropped during quiet hours");
+            return;
+        }
+        QuietDecision::Defer(until) => {
+            info!(
+                kind = %event.kind(),
+                user_id = event.user_id,
+                until = %until.to_rfc3339(),
+                "event deferred until quiet hours end"
+            );
+            // The outbox keeps deferred events across restarts.
+            if router.outbox.is_some() {
+                router.route(event, Some(until)).await;
+            } else {
+                deferred.push((until, event));
+            }
+            return;
+        }
+        QuietDecision::NextHeartbeat => event.quiet_hours = true,
+    }
+    router.route(event, None).await;
+}
+
+async fn sleep_until(at: DateTime<Utc>) {
+    tokio::time::sleep((at - Utc::now()).to_std().unwrap_or_default()).await;
+}
+
+async fn run_direct(
+    sink: String,
+    mut rx: mpsc::Receiver<DiscordStatusChangedEvent>,
+    sender: watch::Receiver<Arc<dyn WebhookSender>>,
+    dry_run: bool,
+) {
+    while let Some(event) = rx.recv().await {
+        let sender = sender.borrow().clone();
+        let result = if dry_run {
+            sender.send(&event).await
+        } else {
+            send_timed(&sink, sender.as_ref(), &event).await
+        };
+        match result {
+            // The sink already logged the request it would have sent.
+            Ok(()) if dry_run => metrics().record_dry_run(&sink),
+            Ok(()) => log_delivered(&sink, &event),
+            Err(err) => {
+                error!(
+                    sink = %sink,
+                    user_id = event.user_id,
+                    error = ?err,
+                    "webhook delivery failed"
+                );
+            }
+        }
+    }
+}
+
+/// Delivers the sink's outbox queue. `wake` signals new entries; the worker stops
+/// once it is closed and nothing is due.
+async fn run_with_outbox(
+    sink: String,
+    mut wake: mpsc::Receiver<()>,
+    sender: watch::Receiver<Arc<dyn WebhookSender>>,
+    outbox: Arc<Outbox>,
+) {
+    match outbox.pending_count(&sink).await {
+        Ok(0) => {}
+        Ok(pending) => info!(sink = %sink, pending, "resuming pending outbox deliveries"),
+        Err(err) => warn!(sink = %sink, error = ?err, "failed to count pending outbox events"),
+    }
+
+    loop {
+        let head = match outbox.peek(&sink).await {
+            Ok(head) => head,
+            Err(err) => {
+                error!(sink = %sink, error = ?err, "failed to read outbox");
+                tokio::time::sleep(OUTBOX_ERROR_RETRY_DELAY).await;
+                continue;
+            }
+        };
+
+        let Some(entry) = head else {
+            match wake.recv().await {
+                Some(()) => continue,
+                None => break,
+            }
+        };
+
+        let wait_seconds = entry.next_attempt_at.saturating_sub(now_unix_seconds());
+        if wait_seconds > 0 {
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(wait_seconds as u64)) => {}
+                woken = wake.recv() => if woken.is_none() {
+                    break;
+                },
+            }
+            continue;
+        }
+
+        let sender = sender.borrow().clone();
+        deliver_entry(&sink, &outbox, sender.as_ref(), &entry).await;
+    }
+}
+
+async fn deliver_entry(
+    sink: &str,
+    outbox: &Outbox,
+    sender: &dyn WebhookSender,
+    entry: &OutboxEntry,
+) {
+    let event = &entry.event;
+    let error_text = match send_timed(sink, sender, event).await {
+        Ok(()) => {
+            log_delivered(sink, event);
+            if let Err(err) = outbox.mark_delivered(entry.id).await {
+                error!(sink, id = entry.id, error = ?err, "failed to remove delivered outbox event");
+                tokio::time::sleep(OUTBOX_ERROR_RETRY_DELAY).await;
+            }
+            return;
+        }
+        Err(err) => format!("{err:#}"),
+    };
+
+    match outbox.mark_failed(entry, &error_text).await {
+        Ok(FailureOutcome::Retry { next_attempt_at }) => {
+            warn!(
+                sink,
+                id = entry.id,
+                user_id = event.user_id,
+                attempts = entry.attempts + 1,
+                max_attempts = outbox.max_attempts(),
+                retry_in_seconds = next_attempt_at.saturating_sub(now_unix_seconds()),
+                error = %error_text,
+                "webhook delivery failed, will retry"
+            );
+        }
+        Ok(FailureOutcome::DeadLettered) => {
+            error!(
+                sink,
+                id = entry.id,
+                user_id = event.user_id,
+                attempts = entry.attempts + 1,
+                error = %error_text,
+                "webhook delivery failed permanently, moved to dead letters"
+            );
+        }
+        Err(err) => {
+            error!(sink, id = entry.id, error = ?err, "failed to record outbox delivery failure");
+            tokio::time::sleep(OUTBOX_ERROR_RETRY_DELAY).await;
+        }
+    }
+}
+
+async fn send_timed(
+    sink: &str,
+    sender: &dyn WebhookSender,
+    event: &DiscordStatusChangedEvent,
+) -> Result<()> {
+    let started = Instant::now();
+    let result = sender.send(event).await;
+    metrics().record_delivery(sink, result.is_ok(), started.elapsed());
+    result
+}
+
+async fn enqueue(
+    outbox: &Outbox,
+    sink: &str,
+    event: &DiscordStatusChangedEvent,
+    deliver_at: Option<DateTime<Utc>>,
+) {
+    let result = match deliver_at {
+        Some(deliver_at) => {
+            outbox
+                .enqueue_deferred(sink, event, deliver_at.timestamp())
+                .await
+        }
+        None => outbox.enqueue(sink, event).await,
+    };
+    if let Err(err) = result {
+        error!(
+            sink,
+            user_id = event.user_id,
+            error = ?err,
+            "failed to persist event to outbox, event dropped"
+        );
+    }
+}
+
+fn log_delivered(sink: &str, event: &DiscordStatusChangedEvent) {
+    let activity_name = event.activity.as_ref().map(|a| a.name.as_str());
+    let steam_app_id = event.activity.as_ref().and_then(|a| a.steam_app_id);
+    info!(
+        sink,
+        kind = %event.kind(),
+        user_id = event.user_id,
+        status = %event.current_status,
+        has_activity = event.activity.is_some(),
+        activity_name = ?activity_name,
+        steam_app_id = ?steam_app_id,
+        reminder = event.reminder.is_some(),
+        "webhook delivered"
+    );
+}
@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Local, NaiveTime, TimeDelta, TimeZone, Utc};
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+use crate::config::DigestSettings;
+use crate::discord::PresenceBoard;
+use crate::event::{
+    DigestActivityTotal, DigestContext, DigestPeriod, DigestStatusTotal, DiscordStatus,
+    DiscordStatusChangedEvent,
+};
+use crate::history::{SessionHistory, SessionSpan};
+
+/// Emits a digest event per target at the configured local time, summarizing the
+/// recorded presence sessions of the past day (and, on the weekly day, the past
+/// week).
+pub async fn run(
+    settings: DigestSettings,
+    board: PresenceBoard,
+    history: Arc<SessionHistory>,
+    tx: mpsc::Sender<DiscordStatusChangedEvent>,
+) -> Result<()> {
+    let time = settings.local_time()?;
+    let mut after = Local::now();
+
+    loop {
+        let fire_at = next_fire_time(&after, time);
+        info!(next_digest_at = %fire_at.to_rfc3339(), "digest scheduled");
+        let wait = (fire_at - Local::now()).to_std().unwrap_or_default();
+        tokio::time::sleep(wait).await;
+
+        let period_end = fire_at.with_timezone(&Utc);
+        for period in due_periods(&settings, &fire_at) {
+            let period_start = period_end - period_length(period);
+            for target in board.snapshot().await {
+                let spans = match history
+                    .spans(target.user_id, target.guild_id, period_start, period_end)
+                    .await
+                {
+                    Ok(spans) => spans,
+                    Err(err) => {
+                        warn!(user_id = target.user_id, error = ?err, "failed to load presence sessions for digest");
+                        continue;
+                    }
+                };
+                if spans.is_empty() {
+                    debug!(
+                        user_id = target.user_id,
+                        period = %period,
+                        "no presence sessions in digest period, skipping"
+                    );
+                    continue;
+                }
+
+                let digest = build_digest(period, period_start, period_end, &spans, settings.top_n);
+                let event = DiscordStatusChangedEvent::new_digest(
+                    target.user_id,
+                    target.guild_id,
+                    target.status.unwrap_or(DiscordStatus::Unknown),
+                    digest,
+                );
+                if tx.send(event).await.is_err() {
+                    return Ok(());
+                }
+            }
+        }
+
+        after = fire_at;
+    }
+}
+
+fn period_length(period: DigestPeriod) -> TimeDelta {
+    match period {
+        DigestPeriod::Daily => TimeDelta::days(1),
+        DigestPeriod::Weekly => TimeDelta::days(7),
+    }
+}
+
+fn due_periods<Tz: TimeZone>(
+    settings: &DigestSettings,
+    fire_at: &DateTime<Tz>,
+) -> Vec<DigestPeriod> {
+    let mut periods = Vec::new();
+    if settings.daily {
+        periods.push(DigestPeriod::Daily);
+    }
+    if settings.weekly && fire_at.weekday() == settings.weekly_day {
+        periods.push(DigestPeriod::Weekly);
+    }
+    periods
+}
+
+/// First occurrence of `time` strictly after `after`. Local times skipped by a DST
+/// change move on to the next day; ambiguous ones use the earlier instant.
+fn next_fire_time<Tz: TimeZone>(after: &DateTime<Tz>, time: NaiveTime) -> DateTime<Tz> {
+    let timezone = after.timezone();
+    let mut date = after.date_naive();
+    loop {
+        if let Some(candidate) = timezone
+            .from_local_datetime(&date.and_time(time))
+            .earliest()
+            && candidate > *after
+        {
+            return candidate;
+        }
+        date = date
+            .succ_opt()
+            .expect("date overflow while scheduling digest");
+    }
+}
+
+fn build_digest(
+    period: DigestPeriod,
+    period_start: DateTime<Utc>,
+    period_end: DateTime<Utc>,
+    spans: &[SessionSpan],
+    top_n: usize,
+) -> DigestContext {
+    let mut online_seconds = 0;
+    let mut statuses: Vec<DigestStatusTotal> = Vec::new();
+    let mut activities: HashMap<&str, DigestActivityTotal> = HashMap::new();
+    let mut steam_games: HashMap<u32, DigestActivityTotal> = HashMap::new();
+
+    for span in spans {
+        if matches!(
+            span.status,
+            DiscordStatus::Online | DiscordStatus::Idle | DiscordStatus::Dnd
+        ) {
+            online_seconds += span.seconds;
+        }
+        match statuses
+            .iter_mut()
+            .find(|total| total.status == span.status)
+        {
+            Some(total) => total.seconds += span.seconds,
+            None => statuses.push(DigestStatusTotal {
+                status: span.status,
+                seconds: span.seconds,
+            }),
+        }
+
+        let Some(name) = span.activity_name.as_deref() else {
+            continue;
+        };
+        let activity = activities
+            .entry(name)
+            .or_insert_with(|| DigestActivityTotal {
+                name: name.to_string(),
+                steam_app_id: None,
+                seconds: 0,
+            });
+        activity.seconds += span.seconds;
+        activity.steam_app_id = activity.steam_app_id.or(span.steam_app_id);
+
+        if let Some(app_id) = span.steam_app_id {
+            steam_games
+                .entry(app_id)
+                .or_insert_with(|| DigestActivityTotal {
+                    name: name.to_string(),
+                    steam_app_id: Some(app_id),
+                    seconds: 0,
+                })
+                .seconds += span.seconds;
+        }
+    }
+
+    statuses.sort_by_key(|total| std::cmp::Reverse(total.seconds));
+    DigestContext {
+        period,
+        period_start,
+        period_end,
+        online_seconds,
+        statuses,
+        activities: top_totals(activities.into_values(), top_n),
+        steam_games: top_totals(steam_games.into_values(), top_n),
+    }
+}
+
+fn top_totals(
+    totals: impl Iterator<Item = DigestActivityTotal>,
+    top_n: usize,
+) -> Vec<DigestActivityTotal> {
+    let mut totals: Vec<_> = totals.filter(|total| total.seconds > 0).collect();
+    totals.sort_by(|a, b| b.seconds.cmp(&a.seconds).then_with(|| a.name.cmp(&b.name)));
+    totals.truncate(top_n);
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, Weekday};
+
+    use super::*;
+
+    fn span(
+        status: DiscordStatus,
+        activity: Option<(&str, Option<u32>)>,
+        seconds: u64,
+    ) -> SessionSpan {
+        SessionSpan {
+            status,
+            activity_name: activity.map(|(name, _)| name.to_string()),
+            steam_app_id: activity.and_then(|(_, app_id)| app_id),
+            seconds,
+        }
+    }
+
+    #[test]
+    fn next_fire_time_rolls_over_to_next_day() {
+        let tz = FixedOffset::east_opt(8 * 3600).expect("valid offset");
+        let time = NaiveTime::from_hms_opt(21, 0, 0).expect("valid time");
+
+        let before = tz
+            .with_ymd_and_hms(2026, 2, 10, 20, 59, 0)
+            .single()
+            .expect("unambiguous local time");
+        assert_eq!(
+            next_fire_time(&before, time),
+            tz.with_ymd_and_hms(2026, 2, 10, 21, 0, 0)
+                .single()
+                .expect("unambiguous local time")
+        );
+
+        let exactly = tz
+            .with_ymd_and_hms(2026, 2, 10, 21, 0, 0)
+            .single()
+            .expect("unambiguous local time");
+        assert_eq!(
+            next_fire_time(&exactly, time),
+            tz.with_ymd_and_hms(2026, 2, 11, 21, 0, 0)
+                .single()
+                .expect("unambiguous local time")
+        );
+    }
+
+    #[test]
+    fn weekly_digest_only_on_configured_day() {
+        let settings = DigestSettings {
+            weekly: true,
+            weekly_day: Weekday::Sun,
+            ..DigestSettings::default()
+        };
+        let tz = FixedOffset::east_opt(0).expect("valid offset");
+        let saturday = tz
+            .with_ymd_and_hms(2026, 2, 14, 21, 0, 0)
+            .single()
+            .expect("unambiguous local time");
+        let sunday = tz
+            .with_ymd_and_hms(2026, 2, 15, 21, 0, 0)
+            .single()
+            .expect("unambiguous local time");
+
+        assert_eq!(due_periods(&settings, &saturday), vec![DigestPeriod::Daily]);
+        assert_eq!(
+            due_periods(&settings, &sunday),
+            vec![DigestPeriod::Daily, DigestPeriod::Weekly]
+        );
+    }
+
+    #[test]
+    fn build_digest_sums_statuses_and_activities() {
+        let end = Utc::now();
+        let spans = vec![
+            span(DiscordStatus::Online, Some(("Dota 2", Some(570))), 3600),
+            span(DiscordStatus::Idle, None, 600),
+            span(
+                DiscordStatus::Online,
+                Some(("Visual Studio Code", None)),
+                1800,
+            ),
+            span(DiscordStatus::Dnd, Some(("Dota 2", Some(570))), 1200),
+            span(DiscordStatus::Offline, None, 7200),
+        ];
+
+        let digest = build_digest(
+            DigestPeriod::Daily,
+            end - TimeDelta::days(1),
+            end,
+            &spans,
+            1,
+        );
+
+        assert_eq!(digest.online_seconds, 7200);
+        assert_eq!(
+            digest.statuses,
+            vec![
+                DigestStatusTotal {
+                    status: DiscordStatus::Offline,
+                    seconds: 7200
+                },
+                DigestStatusTotal {
+                    status: DiscordStatus::Online,
+                    seconds: 5400
+                },
+                DigestStatusTotal {
+                    status: DiscordStatus::Dnd,
+                    seconds: 1200
+                },
+                DigestStatusTotal {
+                    status: DiscordStatus::Idle,
+                    seconds: 600
+                },
+            ]
+        );
+        assert_eq!(
+            digest.activities,
+            vec![DigestActivityTotal {
+                name: "Dota 2".to_string(),
+                steam_app_id: Some(570),
+                seconds: 4800
+            }]
+        );
+        assert_eq!(digest.steam_games, digest.activities);
+    }
+}
@@ -0,0 +1,243 @@
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::event::{DiscordActivity, DiscordStatusChangedEvent};
+
+/// How long a change of one target must stay stable before it is emitted.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct DebouncePolicy {
+    pub status_window: TimeDelta,
+    pub activity_window: TimeDelta,
+    /// Whether activity differences alone still make a coalesced change worth emitting.
+    pub emit_on_activity_change: bool,
+}
+
+impl DebouncePolicy {
+    pub fn is_enabled(&self) -> bool {
+        self.status_window > TimeDelta::zero() || self.activity_window > TimeDelta::zero()
+    }
+
+    fn window_for(&self, event: &DiscordStatusChangedEvent) -> TimeDelta {
+        if event.previous_status != Some(event.current_status) {
+            self.status_window
+        } else {
+            self.activity_window
+        }
+    }
+}
+
+/// A change held back until it has been stable for its debounce window.
+#[derive(Debug, Clone)]
+pub(super) struct PendingChange {
+    event: DiscordStatusChangedEvent,
+    /// Activities and fingerprint as of the last change that was let through.
+    baseline_activities: Vec<DiscordActivity>,
+    baseline_fingerprint: Option<String>,
+    pub due_at: DateTime<Utc>,
+}
+
+/// Runs a candidate event through the debounce stage. Returns the event when it
+/// can be emitted right away; otherwise it is kept (or merged) in `pending`.
+///
+/// Events merged into a pending change keep the previous status, activities and
+/// previous platform / custom status / track of the first change, so flapping
+/// back and forth either coalesces into one event or, when the presence ends up
+/// where it started, into none.
+pub(super) fn debounce(
+    pending: &mut Option<PendingChange>,
+    policy: &DebouncePolicy,
+    event: DiscordStatusChangedEvent,
+    previous_activities: &[DiscordActivity],
+    previous_fingerprint: Option<String>,
+    fingerprint: &str,
+    now: DateTime<Utc>,
+) -> Option<DiscordStatusChangedEvent> {
+    let (event, baseline_activities, baseline_fingerprint) = match pending.take() {
+        Some(previous) => {
+            let event = merge(&previous, event);
+            if is_unchanged(
+                &event,
+                previous.baseline_fingerprint.as_deref(),
+                fingerprint,
+                policy.emit_on_activity_change,
+            ) {
+                return None;
+            }
+            (
+                event,
+                previous.baseline_activities,
+                previous.baseline_fingerprint,
+            )
+        }
+        None => (event, previous_activities.to_vec(), previous_fingerprint),
+    };
+
+    let window = policy.window_for(&event);
+    if window <= TimeDelta::zero() {
+        return Some(event);
+    }
+    *pending = Some(PendingChange {
+        event,
+        baseline_activities,
+        baseline_fingerprint,
+        due_at: now + window,
+    });
+    None
+}
+
+/// Brings a pending change up to date with a presence that is not a change of its
+/// own, such as an activity update that is not emitted. Its due time and
+/// observation time are kept.
+pub(super) fn refresh(pending: &mut Option<PendingChange>, event: DiscordStatusChangedEvent) {
+    if let Some(pending) = pending {
+        let observed_at = pending.event.observed_at;
+        pending.event = merge(pending, event).with_observed_at(observed_at);
+    }
+}
+
+/// Takes the pending change once it has been stable for its window.
+pub(super) fn take_due(
+    pending: &mut Option<PendingChange>,
+    now: DateTime<Utc>,
+) -> Option<DiscordStatusChangedEvent> {
+    if pending.as_ref().is_some_and(|change| change.due_at <= now) {
+        pending.take().map(|change| change.event)
+    } else {
+        None
+    }
+}
+
+fn merge(
+    pending: &PendingChange,
+    mut event: DiscordStatusChangedEvent,
+) -> DiscordStatusChangedEvent {
+    let original = &pending.event;
+    event.previous_status = original.previous_status;
+    event.previous_client_status = original
+        .previous_client_status
+        .or(event.previous_client_status)
+        .filter(|previous| *previous != event.client_status.unwrap_or_default());
+    event.previous_custom_status = original
+        .previous_custom_status
+        .clone()
+        .or(event.previous_custom_status)
+        .filter(|previous| *previous != event.custom_status.clone().unwrap_or_default());
+    event.previous_music = original
+        .previous_music
+        .clone()
+        .or(event.previous_music)
+        .filter(|previous| {
+            event
+                .music
+                .as_ref()
+                .is_none_or(|music| !music.is_same_track(previous))
+        });
+    let activities = std::mem::take(&mut event.activities);
+    event.with_activities(activities, &pending.baseline_activities)
+}
+
+fn is_unchanged(
+    event: &DiscordStatusChangedEvent,
+    baseline_fingerprint: Option<&str>,
+    fingerprint: &str,
+    emit_on_activity_change: bool,
+) -> bool {
+    event.previous_status == Some(event.current_status)
+        && (!emit_on_activity_change || baseline_fingerprint == Some(fingerprint))
+        && event.previous_client_status.is_none()
+        && event.previous_custom_status.is_none()
+        && event.previous_music.is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::DiscordStatus;
+
+    fn policy() -> DebouncePolicy {
+        DebouncePolicy {
+            status_window: TimeDelta::seconds(30),
+            activity_window: TimeDelta::zero(),
+            emit_on_activity_change: true,
+        }
+    }
+
+    fn status_event(previous: DiscordStatus, current: DiscordStatus) -> DiscordStatusChangedEvent {
+        DiscordStatusChangedEvent::new(42, None, Some(previous), current, None, None)
+    }
+
+    #[test]
+    fn coalesces_status_changes_and_keeps_original_previous() {
+        let now = Utc::now();
+        let mut pending = None;
+        let first = debounce(
+            &mut pending,
+            &policy(),
+            status_event(DiscordStatus::Online, DiscordStatus::Idle),
+            &[],
+            Some(String::new()),
+            "",
+            now,
+        );
+        assert!(first.is_none());
+
+        let second = debounce(
+            &mut pending,
+            &policy(),
+            status_event(DiscordStatus::Idle, DiscordStatus::Dnd),
+            &[],
+            Some(String::new()),
+            "",
+            now + TimeDelta::seconds(10),
+        );
+        assert!(second.is_none());
+        assert!(take_due(&mut pending, now + TimeDelta::seconds(30)).is_none());
+
+        let event = take_due(&mut pending, now + TimeDelta::seconds(40))
+            .expect("change should be due after the window");
+        assert_eq!(event.previous_status, Some(DiscordStatus::Online));
+        assert_eq!(event.current_status, DiscordStatus::Dnd);
+        assert!(pending.is_none());
+    }
+
+    #[test]
+    fn flapping_back_to_the_original_status_emits_nothing() {
+        let now = Utc::now();
+        let mut pending = None;
+        debounce(
+            &mut pending,
+            &policy(),
+            status_event(DiscordStatus::Online, DiscordStatus::Idle),
+            &[],
+            Some(String::new()),
+            "",
+            now,
+        );
+        let back = debounce(
+            &mut pending,
+            &policy(),
+            status_event(DiscordStatus::Idle, DiscordStatus::Online),
+            &[],
+            Some(String::new()),
+            "",
+            now + TimeDelta::seconds(5),
+        );
+        assert!(back.is_none());
+        assert!(pending.is_none());
+    }
+
+    #[test]
+    fn zero_window_emits_immediately() {
+        let mut pending = None;
+        let event = debounce(
+            &mut pending,
+            &policy(),
+            status_event(DiscordStatus::Online, DiscordStatus::Online),
+            &[],
+            Some("a".to_string()),
+            "b",
+            Utc::now(),
+        );
+        assert!(event.is_some());
+        assert!(pending.is_none());
+    }
+}
@@ -1,266 +1,374 @@
+mod debounce;
+mod reminder;
+mod replay;
+mod tracker;
+
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
 use anyhow::{Context as AnyhowContext, Result};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
 use serenity::all::{
-    Activity, ActivityType, Client, Context, EventHandler, GatewayIntents, GuildId, OnlineStatus,
-    Presence, Ready, UserId,
+    Activity, ActivityType, Client, ClientStatus, Context, EventHandler, GatewayIntents, GuildId,
+    OnlineStatus, Presence, Ready, ResumedEvent, UserId,
 };
 use serenity::async_trait;
-use tokio::sync::{Mutex, mpsc};
-use tokio::time::MissedTickBehavior;
-use tracing::{debug, error, info, warn};
-
-use crate::config::{DiscordSettings, ReminderSettings};
+use tokio::sync::{Mutex, mpsc, watch};
+use tokio::time::{Interval, MissedTickBehavior};
+use tracing::{debug, info, warn};
+
+use self::reminder::Reminders;
+pub use self::replay::{PresenceRecord, PresenceRecorder, read_records, replay};
+use self::tracker::{Clock, ObservedPresence, PresenceTracker, SystemClock};
+use crate::config::{DiscordSettings, DiscordTargetSettings, ReminderSettings};
 use crate::event::{
-    DiscordActivityContext, DiscordStatus, DiscordStatusChangedEvent, ReminderContext,
+    ActivityAssets, ActivityButton, ActivityKind, ActivityTimestamps, DiscordActivity,
+    DiscordActivityContext, DiscordClientStatus, DiscordCustomStatus, DiscordStatus,
+    DiscordStatusChangedEvent, MusicContext,
 };
+use crate::history::SessionHistory;
+use crate::metrics::metrics;
+use crate::quiet_hours::QuietHours;
 use crate::state_cache::PersistentStatusCache;
-use crate::webhook::WebhookSender;
+
+/// Shared handle to the live presence state of every monitored target.
+#[derive(Clone)]
+pub struct PresenceBoard {
+    targets: Arc<[Arc<TargetMonitor>]>,
+}
+
+impl PresenceBoard {
+    pub async fn restore(
+        settings: &DiscordSettings,
+        reminder: &ReminderSettings,
+        state_cache: Option<&PersistentStatusCache>,
+    ) -> Result<Self> {
+        Self::restore_with_clock(settings, reminder, state_cache, Arc::new(SystemClock)).await
+    }
+
+    async fn restore_with_clock(
+        settings: &DiscordSettings,
+        reminder: &ReminderSettings,
+        state_cache: Option<&PersistentStatusCache>,
+        clock: Arc<dyn Clock>,
+    ) -> Result<Self> {
+        let mut targets = Vec::new();
+        for target in settings.targets() {
+            let reminder = target.reminder.clone().unwrap_or_else(|| reminder.clone());
+            targets.push(Arc::new(
+                TargetMonitor::restore(target, reminder, state_cache, clock.clone()).await?,
+            ));
+        }
+        Ok(Self {
+            targets: targets.into(),
+        })
+    }
+
+    /// Applies reloaded reminder settings to every target in place. Reminder
+    /// anchors whose key is unchanged keep running; nothing is applied when any
+    /// target's settings fail to compile. Targets cannot be added or removed
+    /// without a restart.
+    pub async fn reload_reminders(
+        &self,
+        settings: &DiscordSettings,
+        reminder: &ReminderSettings,
+    ) -> Result<()> {
+        let targets = settings.targets();
+        let mut updates = Vec::with_capacity(self.targets.len());
+        for target in self.targets.iter() {
+            let Some(settings) = targets.iter().find(|settings| {
+                make_status_cache_key(settings.user_id, settings.guild_id)
+                    == target.status_cache_key
+            }) else {
+                warn!(
+                    key = %target.status_cache_key,
+                    "Discord target removed from configuration, restart to stop monitoring it"
+                );
+                continue;
+            };
+            let reminder = settings
+                .reminder
+                .clone()
+                .unwrap_or_else(|| reminder.clone());
+            updates.push((target, Reminders::new(reminder)?));
+        }
+        for settings in &targets {
+            let key = make_status_cache_key(settings.user_id, settings.guild_id);
+            if !self
+                .targets
+                .iter()
+                .any(|target| target.status_cache_key == key)
+            {
+                warn!(
+                    key = %key,
+                    "Discord target added to configuration, restart to start monitoring it"
+                );
+            }
+        }
+
+        for (target, reminders) in updates {
+            target.tracker.lock().await.reload_reminders(reminders);
+        }
+        Ok(())
+    }
+
+    pub async fn snapshot(&self) -> Vec<TargetStatusSnapshot> {
+        let mut snapshots = Vec::with_capacity(self.targets.len());
+        for target in self.targets.iter() {
+            snapshots.push(target.tracker.lock().await.snapshot());
+        }
+        snapshots
+    }
+}
+
+/// Point-in-time view of one target, as exposed by the HTTP API.
+#[derive(Debug, Clone, Serialize)]
+pub struct TargetStatusSnapshot {
+    pub user_id: u64,
+    pub guild_id: Option<u64>,
+    pub current_guild_id: Option<u64>,
+    pub status: Option<DiscordStatus>,
+    pub client_status: Option<DiscordClientStatus>,
+    pub custom_status: Option<DiscordCustomStatus>,
+    pub activity: Option<DiscordActivityContext>,
+    pub activities: Vec<DiscordActivity>,
+    pub music: Option<MusicContext>,
+    pub steam_app_id: Option<u32>,
+    pub reminder_anchor: Option<ReminderAnchorSnapshot>,
+    pub observed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReminderAnchorSnapshot {
+    pub key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub last_sequence: u64,
+}
 
 pub async fn run(
     settings: DiscordSettings,
-    reminder: ReminderSettings,
-    sender: Arc<dyn WebhookSender>,
+    board: PresenceBoard,
+    tx: mpsc::Sender<DiscordStatusChangedEvent>,
     state_cache: Option<Arc<PersistentStatusCache>>,
+    history: Option<Arc<SessionHistory>>,
+    quiet_hours: watch::Receiver<Option<Arc<QuietHours>>>,
+    recorder: Option<Arc<PresenceRecorder>>,
 ) -> Result<()> {
-    let (tx, mut rx) = mpsc::channel::<DiscordStatusChangedEvent>(256);
-    let target_user_id = UserId::new(settings.user_id);
-    let target_guild_id = settings.guild_id.map(GuildId::new);
-    let status_cache_key = make_status_cache_key(settings.user_id, settings.guild_id);
-    let initial_status = if let Some(cache) = state_cache.as_ref() {
-        cache.get_status(&status_cache_key).await
-    } else {
-        None
-    };
-    if let Some(status) = initial_status {
-        info!(
-            key = %status_cache_key,
-            status = %status,
-            "restored persisted status cache"
-        );
-    }
-
-    let runtime_state = Arc::new(Mutex::new(build_initial_runtime_state(
-        initial_status,
-        settings.rich_presence_only,
-        &reminder,
-    )));
+    let targets = board.targets.to_vec();
 
     let handler = PresenceEventHandler {
-        target_user_id,
-        target_guild_id,
-        emit_initial_status: settings.emit_initial_status,
-        emit_on_activity_change: settings.emit_on_activity_change,
-        rich_presence_only: settings.rich_presence_only,
-        reminder: reminder.clone(),
+        targets: targets.clone(),
         tx: tx.clone(),
-        runtime_state: runtime_state.clone(),
         state_cache,
-        status_cache_key,
+        history,
+        recorder,
+        connected: AtomicBool::new(false),
     };
 
-    let reminder_loop = if reminder.enabled {
-        Some(tokio::spawn(run_reminder_loop(
-            reminder.clone(),
-            target_user_id.get(),
+    // Reminder loops run for every target, as reminders can be enabled on reload.
+    let mut background_loops = Vec::new();
+    for target in &targets {
+        background_loops.push(tokio::spawn(run_reminder_loop(
+            target.clone(),
             tx.clone(),
-            runtime_state,
-        )))
-    } else {
-        None
-    };
+            quiet_hours.clone(),
+        )));
+        if target.tracker.lock().await.debounce_enabled() {
+            background_loops.push(tokio::spawn(run_debounce_loop(target.clone(), tx.clone())));
+        }
+    }
 
     let intents = GatewayIntents::GUILDS | GatewayIntents::GUILD_PRESENCES;
-    let mut client = Client::builder(&settings.bot_token, intents)
+    let mut client = Client::builder(settings.bot_token.expose(), intents)
         .event_handler(handler)
         .await
         .context("failed to create Discord client")?;
 
-    let send_loop = tokio::spawn(async move {
-        while let Some(event) = rx.recv().await {
-            match sender.send(&event).await {
-                Ok(()) => {
-                    let activity_name = event.activity.as_ref().map(|a| a.name.as_str());
-                    let steam_app_id = event.activity.as_ref().and_then(|a| a.steam_app_id);
-                    info!(
-                        user_id = event.user_id,
-                        status = %event.current_status,
-                        has_activity = event.activity.is_some(),
-                        activity_name = ?activity_name,
-                        steam_app_id = ?steam_app_id,
-                        reminder = event.reminder.is_some(),
-                        "webhook delivered"
-                    );
-                }
-                Err(err) => {
-                    error!(
-                        user_id = event.user_id,
-                        error = ?err,
-                        "webhook delivery failed"
-                    );
-                }
-            }
-        }
-    });
-
-    info!(
-        user_id = settings.user_id,
-        guild_id = ?settings.guild_id,
-        emit_on_activity_change = settings.emit_on_activity_change,
-        rich_presence_only = settings.rich_presence_only,
-        reminder_enabled = reminder.enabled,
-        reminder_interval_minutes = reminder.interval_minutes,
-        reminder_steam_only = reminder.steam_only,
-        "starting Discord presence monitor"
-    );
+    for target in &targets {
+        let tracker = target.tracker.lock().await;
+        let settings = tracker.settings();
+        let reminders = tracker.reminders();
+        info!(
+            user_id = settings.user_id,
+            guild_id = ?settings.guild_id,
+            emit_on_activity_change = settings.emit_on_activity_change,
+            rich_presence_only = settings.rich_presence_only,
+            emit_on_track_change = settings.emit_on_track_change,
+            reminder_enabled = reminders.settings.enabled,
+            reminder_interval_minutes = reminders.settings.interval_minutes,
+            reminder_steam_only = reminders.settings.steam_only,
+            reminder_rules = reminders.settings.rules.len(),
+            "monitoring Discord target"
+        );
+    }
+    info!(targets = targets.len(), "starting Discord presence monitor");
     let client_result = client
         .start()
         .await
         .context("Discord client exited unexpectedly");
 
-    if let Some(handle) = reminder_loop {
+    for handle in background_loops {
         handle.abort();
         let _ = handle.await;
     }
-    drop(tx);
-    drop(client);
-    let _ = send_loop.await;
     client_result
 }
 
-#[derive(Debug, Clone)]
-struct RuntimePresenceState {
-    current_status: Option<DiscordStatus>,
-    current_guild_id: Option<u64>,
-    current_activity: Option<DiscordActivityContext>,
-    current_activity_fingerprint: Option<String>,
-    reminder_anchor: Option<ReminderAnchor>,
-}
-
-#[derive(Debug, Clone)]
-struct ReminderAnchor {
-    key: String,
-    started_at_unix: i64,
-    last_sequence: u64,
+impl ObservedPresence {
+    fn from_gateway(
+        status: OnlineStatus,
+        client_status: Option<&ClientStatus>,
+        activities: &[Activity],
+        now: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            status: normalize_status(status),
+            client_status: client_status.map(convert_client_status),
+            custom_status: extract_custom_status(activities),
+            activity: extract_activity_context(activities),
+            activities: activities
+                .iter()
+                .filter(|activity| activity.kind != ActivityType::Custom)
+                .map(convert_activity)
+                .collect(),
+            music: extract_music(activities).map(|music| music.at(now)),
+            activity_fingerprint: build_activity_fingerprint(activities, false),
+            activity_fingerprint_without_track: build_activity_fingerprint(activities, true),
+        }
+    }
 }
 
-struct PresenceEventHandler {
-    target_user_id: UserId,
-    target_guild_id: Option<GuildId>,
-    emit_initial_status: bool,
-    emit_on_activity_change: bool,
-    rich_presence_only: bool,
-    reminder: ReminderSettings,
-    tx: mpsc::Sender<DiscordStatusChangedEvent>,
-    runtime_state: Arc<Mutex<RuntimePresenceState>>,
-    state_cache: Option<Arc<PersistentStatusCache>>,
+/// One monitored user (optionally scoped to a guild) with its presence tracker.
+struct TargetMonitor {
+    user_id: UserId,
+    guild_id: Option<GuildId>,
+    tracker: Mutex<PresenceTracker>,
     status_cache_key: String,
 }
 
-impl PresenceEventHandler {
-    fn is_target_presence(&self, presence: &Presence) -> bool {
-        if presence.user.id != self.target_user_id {
+impl TargetMonitor {
+    async fn restore(
+        settings: DiscordTargetSettings,
+        reminder: ReminderSettings,
+        state_cache: Option<&PersistentStatusCache>,
+        clock: Arc<dyn Clock>,
+    ) -> Result<Self> {
+        let reminders = Reminders::new(reminder)?;
+        let status_cache_key = make_status_cache_key(settings.user_id, settings.guild_id);
+        let initial_status = match state_cache {
+            Some(cache) => cache.get_status(&status_cache_key).await,
+            None => None,
+        };
+        if let Some(status) = initial_status {
+            info!(
+                key = %status_cache_key,
+                status = %status,
+                "restored persisted status cache"
+            );
+        }
+
+        Ok(Self {
+            user_id: UserId::new(settings.user_id),
+            guild_id: settings.guild_id.map(GuildId::new),
+            tracker: Mutex::new(PresenceTracker::new(
+                settings,
+                reminders,
+                initial_status,
+                clock,
+            )),
+            status_cache_key,
+        })
+    }
+
+    fn matches_presence(&self, user_id: u64, guild_id: Option<u64>) -> bool {
+        if user_id != self.user_id.get() {
             return false;
         }
-        match self.target_guild_id {
-            Some(target_guild_id) => presence.guild_id == Some(target_guild_id),
+        match self.guild_id {
+            Some(target_guild_id) => guild_id == Some(target_guild_id.get()),
             None => true,
         }
     }
+}
+
+struct PresenceEventHandler {
+    targets: Vec<Arc<TargetMonitor>>,
+    tx: mpsc::Sender<DiscordStatusChangedEvent>,
+    state_cache: Option<Arc<PersistentStatusCache>>,
+    history: Option<Arc<SessionHistory>>,
+    /// Captures matched presences for `statushub replay`.
+    recorder: Option<Arc<PresenceRecorder>>,
+    /// Set after the first `Ready`, so later ones count as reconnects.
+    connected: AtomicBool,
+}
 
+impl PresenceEventHandler {
+    /// Feeds one presence to the target's tracker, persists the status and
+    /// session, and sends the resulting events.
     async fn handle_presence_update(
         &self,
+        target: &TargetMonitor,
         guild_id: Option<GuildId>,
-        raw_status: OnlineStatus,
-        activity: Option<DiscordActivityContext>,
-        activity_fingerprint: String,
+        presence: ObservedPresence,
     ) {
-        let next_status = normalize_status(raw_status);
-        let now = Utc::now();
-        let has_activity = !activity_fingerprint.is_empty();
-
-        let (previous, should_emit, status_changed) = {
-            let mut state = self.runtime_state.lock().await;
-            let previous = state.current_status;
-            let status_changed = previous != Some(next_status);
-            let activity_changed = state
-                .current_activity_fingerprint
-                .as_ref()
-                .map(|v| v != &activity_fingerprint)
-                .unwrap_or(!activity_fingerprint.is_empty());
-
-            let next_anchor_key = reminder_anchor_key(
-                &self.reminder,
-                self.rich_presence_only,
-                next_status,
-                activity.as_ref(),
-            );
-            let current_anchor_key = state
-                .reminder_anchor
-                .as_ref()
-                .map(|anchor| anchor.key.as_str());
-            if current_anchor_key != next_anchor_key.as_deref() {
-                state.reminder_anchor = next_anchor_key.map(|key| ReminderAnchor {
-                    key,
-                    started_at_unix: now.timestamp(),
-                    last_sequence: 0,
-                });
-            }
-
-            state.current_status = Some(next_status);
-            state.current_guild_id = guild_id.map(GuildId::get);
-            state.current_activity = activity.clone();
-            state.current_activity_fingerprint = Some(activity_fingerprint.clone());
-
-            let should_emit = should_emit_presence_event(
-                status_changed,
-                activity_changed,
-                self.emit_on_activity_change,
-                self.rich_presence_only,
-                has_activity,
-                self.emit_initial_status,
-                previous.is_some(),
-            );
-            (previous, should_emit, status_changed)
-        };
+        let status = presence.status;
+        let activity = presence.activity.clone();
+        let update = target
+            .tracker
+            .lock()
+            .await
+            .observe(guild_id.map(GuildId::get), presence);
 
-        if status_changed {
-            self.persist_status(next_status).await;
+        if update.status_changed {
+            self.persist_status(target, status).await;
         }
+        self.record_history(target, status, activity.as_ref(), update.observed_at)
+            .await;
 
-        if previous.is_none() && !self.emit_initial_status {
-            info!(status = %next_status, "captured initial status without emitting");
-            return;
-        }
-        if !should_emit {
-            return;
+        for event in update.events {
+            if let Err(err) = self.tx.send(event).await {
+                warn!(error = ?err, "status event channel closed");
+            }
         }
+    }
 
-        let event = DiscordStatusChangedEvent::new(
-            self.target_user_id.get(),
-            guild_id.map(GuildId::get),
-            previous,
-            next_status,
-            activity,
-            None,
-        );
-
-        if let Err(err) = self.tx.send(event).await {
-            warn!(error = ?err, "status event channel closed");
+    async fn record_history(
+        &self,
+        target: &TargetMonitor,
+        status: DiscordStatus,
+        activity: Option<&DiscordActivityContext>,
+        at: DateTime<Utc>,
+    ) {
+        let Some(history) = self.history.as_ref() else {
+            return;
+        };
+        if let Err(err) = history
+            .record(
+                target.user_id.get(),
+                target.guild_id.map(GuildId::get),
+                status,
+                activity,
+                at,
+            )
+            .await
+        {
+            warn!(error = ?err, user_id = target.user_id.get(), "failed to record presence session");
         }
     }
 
-    async fn persist_status(&self, status: DiscordStatus) {
+    async fn persist_status(&self, target: &TargetMonitor, status: DiscordStatus) {
         let Some(state_cache) = self.state_cache.as_ref() else {
             return;
         };
         if let Err(err) = state_cache
-            .set_status(self.status_cache_key.clone(), status)
+            .set_status(target.status_cache_key.clone(), status)
             .await
         {
-            warn!(error = ?err, "failed to persist status cache");
+            warn!(error = ?err, key = %target.status_cache_key, "failed to persist status cache");
         }
     }
 }
@@ -268,6 +376,9 @@ impl PresenceEventHandler {
 #[async_trait]
 impl EventHandler for PresenceEventHandler {
     async fn ready(&self, _: Context, ready: Ready) {
+        if self.connected.swap(true, Ordering::Relaxed) {
+            metrics().gateway_reconnects.inc();
+        }
         info!(
             user = %ready.user.name,
             id = ready.user.id.get(),
@@ -275,51 +386,61 @@ impl EventHandler for PresenceEventHandler {
         );
     }
 
-    async fn cache_ready(&self, ctx: Context, guilds: Vec<GuildId>) {
-        if let Some(target_guild_id) = self.target_guild_id {
-            let initial_presence = ctx.cache.guild(target_guild_id).and_then(|guild| {
-                guild.presences.get(&self.target_user_id).map(|presence| {
-                    (
-                        presence.status,
-                        extract_activity_context(&presence.activities),
-                        build_activity_fingerprint(&presence.activities),
-                    )
-                })
-            });
-            if let Some((status, activity, activity_fingerprint)) = initial_presence {
-                self.handle_presence_update(
-                    Some(target_guild_id),
-                    status,
-                    activity,
-                    activity_fingerprint,
-                )
-                .await;
-            }
-            return;
-        }
+    async fn resume(&self, _: Context, _: ResumedEvent) {
+        metrics().gateway_reconnects.inc();
+        info!("Discord gateway session resumed");
+    }
 
-        for guild_id in guilds {
-            let initial_presence = ctx.cache.guild(guild_id).and_then(|guild| {
-                guild.presences.get(&self.target_user_id).map(|presence| {
-                    (
+    async fn cache_ready(&self, ctx: Context, guilds: Vec<GuildId>) {
+        for target in &self.targets {
+            let candidate_guilds = match target.guild_id {
+                Some(target_guild_id) => vec![target_guild_id],
+                None => guilds.clone(),
+            };
+
+            for guild_id in candidate_guilds {
+                let initial_presence = ctx
+                    .cache
+                    .guild(guild_id)
+                    .and_then(|guild| guild.presences.get(&target.user_id).cloned());
+                if let Some(presence) = initial_presence {
+                    let now = Utc::now();
+                    if let Some(recorder) = &self.recorder {
+                        recorder
+                            .record(&PresenceRecord::from_presence(
+                                &presence,
+                                Some(guild_id),
+                                now,
+                            ))
+                            .await;
+                    }
+                    let observed = ObservedPresence::from_gateway(
                         presence.status,
-                        extract_activity_context(&presence.activities),
-                        build_activity_fingerprint(&presence.activities),
-                    )
-                })
-            });
-            if let Some((status, activity, activity_fingerprint)) = initial_presence {
-                self.handle_presence_update(Some(guild_id), status, activity, activity_fingerprint)
-                    .await;
-                break;
+                        presence.client_status.as_ref(),
+                        &presence.activities,
+                        now,
+                    );
+                    self.handle_presence_update(target, Some(guild_id), observed)
+                        .await;
+                    break;
+                }
             }
         }
     }
 
     async fn presence_update(&self, _: Context, new_data: Presence) {
-        if !self.is_target_presence(&new_data) {
+        let matched: Vec<Arc<TargetMonitor>> = self
+            .targets
+            .iter()
+            .filter(|target| {
+                target.matches_presence(new_data.user.id.get(), new_data.guild_id.map(GuildId::get))
+            })
+            .cloned()
+            .collect();
+        if matched.is_empty() {
             return;
         }
+        metrics().presence_updates.inc();
 
         debug!(
             user_id = new_data.user.id.get(),
@@ -330,67 +451,58 @@ impl EventHandler for PresenceEventHandler {
             "received presence update"
         );
 
-        let activity = extract_activity_context(&new_data.activities);
-        let activity_fingerprint = build_activity_fingerprint(&new_data.activities);
-        self.handle_presence_update(
-            new_data.guild_id,
+        let now = Utc::now();
+        if let Some(recorder) = &self.recorder {
+            recorder
+                .record(&PresenceRecord::from_presence(
+                    &new_data,
+                    new_data.guild_id,
+                    now,
+                ))
+                .await;
+        }
+        let presence = ObservedPresence::from_gateway(
             new_data.status,
-            activity,
-            activity_fingerprint,
-        )
-        .await;
+            new_data.client_status.as_ref(),
+            &new_data.activities,
+            now,
+        );
+        for target in &matched {
+            self.handle_presence_update(target, new_data.guild_id, presence.clone())
+                .await;
+        }
     }
 }
 
 async fn run_reminder_loop(
-    settings: ReminderSettings,
-    target_user_id: u64,
+    target: Arc<TargetMonitor>,
     tx: mpsc::Sender<DiscordStatusChangedEvent>,
-    runtime_state: Arc<Mutex<RuntimePresenceState>>,
+    quiet_hours: watch::Receiver<Option<Arc<QuietHours>>>,
 ) {
-    let mut ticker = tokio::time::interval(Duration::from_secs(settings.check_interval_seconds));
-    ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
-    let interval_seconds = settings.interval_seconds();
+    let mut check_interval_seconds = target
+        .tracker
+        .lock()
+        .await
+        .reminders()
+        .settings
+        .check_interval_seconds;
+    let mut ticker = reminder_ticker(check_interval_seconds);
 
     loop {
         ticker.tick().await;
-        let maybe_event = {
-            let mut state = runtime_state.lock().await;
-            if state.current_status.is_none() || state.reminder_anchor.is_none() {
-                None
-            } else {
-                let current_status = state.current_status.unwrap_or(DiscordStatus::Unknown);
-                let guild_id = state.current_guild_id;
-                let activity = state.current_activity.clone();
-                let anchor = state
-                    .reminder_anchor
-                    .as_mut()
-                    .expect("anchor checked to exist");
-                let elapsed = Utc::now()
-                    .timestamp()
-                    .saturating_sub(anchor.started_at_unix) as u64;
-                let sequence = elapsed / interval_seconds;
-                if sequence == 0 || sequence <= anchor.last_sequence {
-                    None
-                } else {
-                    anchor.last_sequence = sequence;
-                    Some(DiscordStatusChangedEvent::new(
-                        target_user_id,
-                        guild_id,
-                        None,
-                        current_status,
-                        activity,
-                        Some(ReminderContext {
-                            elapsed_seconds: sequence.saturating_mul(interval_seconds),
-                            interval_seconds,
-                            sequence,
-                        }),
-                    ))
-                }
+        let quiet_hours = quiet_hours.borrow().clone();
+        let event = {
+            let mut tracker = target.tracker.lock().await;
+            let interval = tracker.reminders().settings.check_interval_seconds;
+            if interval != check_interval_seconds {
+                check_interval_seconds = interval;
+                ticker = reminder_ticker(check_interval_seconds);
             }
+            tracker.tick_reminder(quiet_hours.as_deref())
         };
 
-        if let Some(event) = maybe_event {
+        if let Some(event) = event {
+            metrics().reminders_fired.inc();
             if tx.send(event).await.is_err() {
                 break;
             }
@@ -398,50 +510,28 @@ async fn run_reminder_loop(
     }
 }
 
-fn build_initial_runtime_state(
-    initial_status: Option<DiscordStatus>,
-    rich_presence_only: bool,
-    reminder: &ReminderSettings,
-) -> RuntimePresenceState {
-    let reminder_anchor = if reminder.enabled && !reminder.steam_only && !rich_presence_only {
-        initial_status.map(|status| ReminderAnchor {
-            key: format!("status:{status}"),
-            started_at_unix: Utc::now().timestamp(),
-            last_sequence: 0,
-        })
-    } else {
-        None
-    };
-
-    RuntimePresenceState {
-        current_status: initial_status,
-        current_guild_id: None,
-        current_activity: None,
-        current_activity_fingerprint: None,
-        reminder_anchor,
-    }
+fn reminder_ticker(check_interval_seconds: u64) -> Interval {
+    let mut ticker = tokio::time::interval(Duration::from_secs(check_interval_seconds));
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    ticker
 }
 
-fn reminder_anchor_key(
-    reminder: &ReminderSettings,
-    rich_presence_only: bool,
-    status: DiscordStatus,
-    activity: Option<&DiscordActivityContext>,
-) -> Option<String> {
-    if !reminder.enabled {
-        return None;
-    }
-
-    if rich_presence_only && activity.is_none() {
-        return None;
-    }
+/// Emits debounced changes once they have been stable for their window.
+async fn run_debounce_loop(
+    target: Arc<TargetMonitor>,
+    tx: mpsc::Sender<DiscordStatusChangedEvent>,
+) {
+    let mut ticker = tokio::time::interval(Duration::from_secs(1));
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
 
-    if reminder.steam_only {
-        activity
-            .and_then(|activity| activity.steam_app_id)
-            .map(|app_id| format!("steam:{app_id}:{status}"))
-    } else {
-        Some(format!("status:{status}"))
+    loop {
+        ticker.tick().await;
+        let due = target.tracker.lock().await.take_debounced();
+        for event in due {
+            if tx.send(event).await.is_err() {
+                return;
+            }
+        }
     }
 }
 
@@ -464,9 +554,143 @@ fn extract_activity_context(activities: &[Activity]) -> Option<DiscordActivityCo
         details: activity.details.clone(),
         state: activity.state.clone(),
         steam_app_id: extract_steam_app_id(activity),
+        started_at: activity
+            .timestamps
+            .as_ref()
+            .and_then(|timestamps| timestamps.start)
+            .and_then(timestamp_from_millis),
+        ends_at: activity
+            .timestamps
+            .as_ref()
+            .and_then(|timestamps| timestamps.end)
+            .and_then(timestamp_from_millis),
+    })
+}
+
+fn convert_activity(activity: &Activity) -> DiscordActivity {
+    DiscordActivity {
+        kind: convert_activity_kind(activity.kind),
+        name: activity.name.clone(),
+        details: activity.details.clone(),
+        state: activity.state.clone(),
+        application_id: activity.application_id.map(|id| id.get()),
+        steam_app_id: extract_steam_app_id(activity),
+        url: activity.url.as_ref().map(|url| url.to_string()),
+        timestamps: activity
+            .timestamps
+            .as_ref()
+            .map(|timestamps| ActivityTimestamps {
+                start: timestamps.start.and_then(timestamp_from_millis),
+                end: timestamps.end.and_then(timestamp_from_millis),
+            }),
+        assets: activity.assets.as_ref().map(|assets| ActivityAssets {
+            large_image: assets.large_image.clone(),
+            large_text: assets.large_text.clone(),
+            small_image: assets.small_image.clone(),
+            small_text: assets.small_text.clone(),
+        }),
+        buttons: activity
+            .buttons
+            .iter()
+            .map(|button| ActivityButton {
+                label: button.label.clone(),
+                url: Some(button.url.clone()).filter(|url| !url.is_empty()),
+            })
+            .collect(),
+    }
+}
+
+fn convert_client_status(client_status: &ClientStatus) -> DiscordClientStatus {
+    DiscordClientStatus {
+        desktop: client_status.desktop.map(normalize_status),
+        mobile: client_status.mobile.map(normalize_status),
+        web: client_status.web.map(normalize_status),
+    }
+}
+
+fn convert_activity_kind(kind: ActivityType) -> ActivityKind {
+    match kind {
+        ActivityType::Playing => ActivityKind::Playing,
+        ActivityType::Streaming => ActivityKind::Streaming,
+        ActivityType::Listening => ActivityKind::Listening,
+        ActivityType::Watching => ActivityKind::Watching,
+        ActivityType::Custom => ActivityKind::Custom,
+        ActivityType::Competing => ActivityKind::Competing,
+        _ => ActivityKind::Unknown,
+    }
+}
+
+fn timestamp_from_millis(millis: u64) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp_millis(i64::try_from(millis).ok()?)
+}
+
+fn extract_custom_status(activities: &[Activity]) -> Option<DiscordCustomStatus> {
+    let activity = activities
+        .iter()
+        .find(|activity| activity.kind == ActivityType::Custom)?;
+    let emoji = activity.emoji.as_ref().map(|emoji| match emoji.id {
+        Some(_) => format!(":{}:", emoji.name),
+        None => emoji.name.clone(),
+    });
+    let text = activity
+        .state
+        .as_deref()
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(str::to_string);
+    if emoji.is_none() && text.is_none() {
+        return None;
+    }
+    Some(DiscordCustomStatus { emoji, text })
+}
+
+fn is_spotify_activity(activity: &Activity) -> bool {
+    activity.kind == ActivityType::Listening
+        && (activity.name == "Spotify"
+            || activity.sync_id.is_some()
+            || activity
+                .assets
+                .as_ref()
+                .and_then(|assets| assets.large_image.as_deref())
+                .is_some_and(|image| image.starts_with("spotify:")))
+}
+
+/// Reads the Spotify track from its `listening` activity: title in `details`,
+/// artists in `state`, album in the large image text and the track id in `sync_id`.
+fn extract_music(activities: &[Activity]) -> Option<MusicContext> {
+    let activity = activities
+        .iter()
+        .find(|activity| is_spotify_activity(activity))?;
+    let track = activity.details.clone()?;
+    let assets = activity.assets.as_ref();
+    let timestamps = activity.timestamps.as_ref();
+    Some(MusicContext {
+        track,
+        artist: activity.state.clone(),
+        album: assets.and_then(|assets| assets.large_text.clone()),
+        track_id: activity.sync_id.clone(),
+        track_url: activity
+            .sync_id
+            .as_ref()
+            .map(|id| format!("https://open.spotify.com/track/{id}")),
+        album_art_url: assets
+            .and_then(|assets| assets.large_image.as_deref())
+            .and_then(|image| image.strip_prefix("spotify:"))
+            .map(|image_id| format!("https://i.scdn.co/image/{image_id}")),
+        started_at: timestamps
+            .and_then(|timestamps| timestamps.start)
+            .and_then(timestamp_from_millis),
+        ends_at: timestamps
+            .and_then(|timestamps| timestamps.end)
+            .and_then(timestamp_from_millis),
+        duration_seconds: None,
+        position_seconds: None,
+        progress: None,
     })
 }
 
+/// Picks the activity that best describes what the user is doing. The custom
+/// status is reported separately and never chosen.
 fn pick_primary_activity(activities: &[Activity]) -> Option<&Activity> {
     activities
         .iter()
@@ -482,7 +706,11 @@ fn pick_primary_activity(activities: &[Activity]) -> Option<&Activity> {
                 )
             })
         })
-        .or_else(|| activities.first())
+        .or_else(|| {
+            activities
+                .iter()
+                .find(|activity| activity.kind != ActivityType::Custom)
+        })
 }
 
 fn extract_steam_app_id(activity: &Activity) -> Option<u32> {
@@ -544,9 +772,17 @@ fn summarize_activities(activities: &[Activity]) -> String {
     }
 }
 
-fn build_activity_fingerprint(activities: &[Activity]) -> String {
+fn build_activity_fingerprint(activities: &[Activity], ignore_track: bool) -> String {
     let mut parts = Vec::with_capacity(activities.len());
     for activity in activities {
+        // Custom status changes have their own trigger.
+        if activity.kind == ActivityType::Custom {
+            continue;
+        }
+        if ignore_track && is_spotify_activity(activity) {
+            parts.push(format!("{:?}|{}", activity.kind, activity.name));
+            continue;
+        }
         let app_id = activity.application_id.map(|id| id.get());
         let assets = activity.assets.as_ref();
         let large = assets.and_then(|a| a.large_image.as_deref()).unwrap_or("-");
@@ -559,23 +795,6 @@ fn build_activity_fingerprint(activities: &[Activity]) -> String {
     parts.join("||")
 }
 
-fn should_emit_presence_event(
-    status_changed: bool,
-    activity_changed: bool,
-    emit_on_activity_change: bool,
-    rich_presence_only: bool,
-    has_activity: bool,
-    emit_initial_status: bool,
-    has_previous_status: bool,
-) -> bool {
-    let trigger_change = if rich_presence_only {
-        emit_on_activity_change && activity_changed && has_activity
-    } else {
-        status_changed || (emit_on_activity_change && activity_changed)
-    };
-    trigger_change && (emit_initial_status || has_previous_status)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -607,54 +826,101 @@ mod tests {
     }
 
     #[test]
-    fn reminder_anchor_key_steam_only() {
-        let settings = ReminderSettings {
-            enabled: true,
-            interval_minutes: 30,
-            steam_only: true,
-            check_interval_seconds: 30,
-        };
-        let key = reminder_anchor_key(
-            &settings,
-            false,
-            DiscordStatus::Online,
-            Some(&DiscordActivityContext {
-                name: "Dota 2".to_string(),
-                details: None,
-                state: None,
-                steam_app_id: Some(570),
-            }),
-        );
-        assert_eq!(key.as_deref(), Some("steam:570:online"));
+    fn summarize_activities_empty() {
+        assert_eq!(summarize_activities(&[]), "[]");
     }
 
     #[test]
-    fn emit_on_activity_change_when_enabled() {
-        assert!(should_emit_presence_event(
-            false, true, true, false, true, false, true
-        ));
-        assert!(!should_emit_presence_event(
-            false, true, false, false, true, false, true
-        ));
+    fn activity_fingerprint_empty_when_no_activities() {
+        assert!(build_activity_fingerprint(&[], false).is_empty());
     }
 
     #[test]
-    fn rich_presence_only_blocks_status_only_events() {
-        assert!(!should_emit_presence_event(
-            true, false, true, true, false, false, true
-        ));
-        assert!(should_emit_presence_event(
-            false, true, true, true, true, false, true
-        ));
-    }
+    fn custom_status_is_separate_from_primary_activity() {
+        let activities: Vec<Activity> = serde_json::from_value(serde_json::json!([
+            {
+                "type": 4,
+                "name": "Custom Status",
+                "state": "lunch break",
+                "emoji": { "name": "🍜" },
+                "created_at": 0
+            },
+            {
+                "type": 0,
+                "name": "Dota 2",
+                "created_at": 0
+            }
+        ]))
+        .expect("activities should deserialize");
 
-    #[test]
-    fn summarize_activities_empty() {
-        assert_eq!(summarize_activities(&[]), "[]");
+        let presence =
+            ObservedPresence::from_gateway(OnlineStatus::Online, None, &activities, Utc::now());
+        assert_eq!(
+            presence.custom_status,
+            Some(DiscordCustomStatus {
+                emoji: Some("🍜".to_string()),
+                text: Some("lunch break".to_string()),
+            })
+        );
+        assert_eq!(
+            presence.activity.map(|activity| activity.name),
+            Some("Dota 2".to_string())
+        );
+        assert_eq!(presence.activities.len(), 1);
+        assert!(!presence.activity_fingerprint.contains("Custom Status"));
     }
 
     #[test]
-    fn activity_fingerprint_empty_when_no_activities() {
-        assert!(build_activity_fingerprint(&[]).is_empty());
+    fn spotify_activity_becomes_music_context() {
+        let spotify = |track: &str, sync_id: &str| {
+            serde_json::json!({
+                "type": 2,
+                "name": "Spotify",
+                "details": track,
+                "state": "Daft Punk",
+                "sync_id": sync_id,
+                "assets": {
+                    "large_image": "spotify:ab67616d0000b273",
+                    "large_text": "Discovery"
+                },
+                "timestamps": { "start": 1_770_000_000_000u64, "end": 1_770_000_200_000u64 },
+                "created_at": 0
+            })
+        };
+        let dota = serde_json::json!({ "type": 0, "name": "Dota 2", "created_at": 0 });
+        let first: Vec<Activity> =
+            serde_json::from_value(serde_json::json!([dota, spotify("One More Time", "a1")]))
+                .expect("activities should deserialize");
+        let second: Vec<Activity> =
+            serde_json::from_value(serde_json::json!([dota, spotify("Digital Love", "b2")]))
+                .expect("activities should deserialize");
+
+        let music = extract_music(&second).expect("spotify activity should be detected");
+        assert_eq!(music.track, "Digital Love");
+        assert_eq!(music.artist.as_deref(), Some("Daft Punk"));
+        assert_eq!(music.album.as_deref(), Some("Discovery"));
+        assert_eq!(
+            music.track_url.as_deref(),
+            Some("https://open.spotify.com/track/b2")
+        );
+        assert_eq!(
+            music.album_art_url.as_deref(),
+            Some("https://i.scdn.co/image/ab67616d0000b273")
+        );
+        assert_eq!(
+            music
+                .at(DateTime::from_timestamp(1_770_000_100, 0).expect("valid timestamp"))
+                .progress,
+            Some(0.5)
+        );
+
+        assert_ne!(
+            build_activity_fingerprint(&first, false),
+            build_activity_fingerprint(&second, false)
+        );
+        assert_eq!(
+            build_activity_fingerprint(&first, true),
+            build_activity_fingerprint(&second, true)
+        );
     }
 }
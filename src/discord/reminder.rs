@@ -0,0 +1,361 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+
+use crate::config::ReminderSettings;
+use crate::event::{DiscordActivityContext, DiscordStatus};
+
+/// When and how often a target is reminded of a state that keeps lasting.
+#[derive(Debug)]
+pub(super) struct ReminderPolicy {
+    /// Rule name; `None` for the policy built from plain `[reminder]` settings.
+    pub name: Option<String>,
+    matcher: Matcher,
+    /// Gaps between reminders in seconds; the last one repeats.
+    intervals: Vec<u64>,
+    max_count: Option<u64>,
+    max_duration_seconds: Option<u64>,
+    emit_on_end: bool,
+}
+
+#[derive(Debug)]
+enum Matcher {
+    /// Any status, anchored on the status alone.
+    Status,
+    /// Steam games only, anchored on the game and status.
+    SteamOnly,
+    Rule {
+        statuses: Vec<DiscordStatus>,
+        activity: Option<Regex>,
+        steam_app_ids: Vec<u32>,
+    },
+}
+
+/// Reminder settings of one target with their compiled policies, replaced as a
+/// whole when the configuration is reloaded.
+#[derive(Debug)]
+pub(super) struct Reminders {
+    pub settings: ReminderSettings,
+    pub policies: Vec<ReminderPolicy>,
+}
+
+impl Reminders {
+    pub fn new(settings: ReminderSettings) -> Result<Self> {
+        Ok(Self {
+            policies: build_policies(&settings)?,
+            settings,
+        })
+    }
+}
+
+/// Compiles the reminder settings of one target. Without `[[reminder.rules]]`
+/// this is a single policy with the plain `interval_minutes` / `steam_only`
+/// behaviour.
+pub(super) fn build_policies(settings: &ReminderSettings) -> Result<Vec<ReminderPolicy>> {
+    if !settings.enabled {
+        return Ok(Vec::new());
+    }
+    if settings.rules.is_empty() {
+        return Ok(vec![ReminderPolicy {
+            name: None,
+            matcher: if settings.steam_only {
+                Matcher::SteamOnly
+            } else {
+                Matcher::Status
+            },
+            intervals: vec![settings.interval_seconds()],
+            max_count: None,
+            max_duration_seconds: None,
+            emit_on_end: false,
+        }]);
+    }
+    settings
+        .rules
+        .iter()
+        .enumerate()
+        .map(|(index, rule)| {
+            let field = format!("reminder.rules[{index}]");
+            Ok(ReminderPolicy {
+                name: Some(rule.name.clone().unwrap_or_else(|| field.clone())),
+                matcher: Matcher::Rule {
+                    statuses: rule.statuses.clone(),
+                    activity: rule
+                        .activity_regex()
+                        .with_context(|| format!("invalid {field}.activity"))?,
+                    steam_app_ids: rule.steam_app_ids.clone(),
+                },
+                intervals: rule.intervals_seconds(settings.interval_minutes),
+                max_count: rule.max_count,
+                max_duration_seconds: rule
+                    .max_duration_minutes
+                    .map(|minutes| minutes.saturating_mul(60)),
+                emit_on_end: rule.emit_on_end,
+            })
+        })
+        .collect()
+}
+
+/// Returns the first policy matching the state, with the key of the anchor it
+/// starts. Reminders restart whenever the key changes.
+pub(super) fn select_policy(
+    policies: &[ReminderPolicy],
+    rich_presence_only: bool,
+    status: DiscordStatus,
+    activity: Option<&DiscordActivityContext>,
+) -> Option<(usize, String)> {
+    if rich_presence_only && activity.is_none() {
+        return None;
+    }
+    policies.iter().enumerate().find_map(|(index, policy)| {
+        policy
+            .anchor_key(index, status, activity)
+            .map(|key| (index, key))
+    })
+}
+
+impl ReminderPolicy {
+    fn anchor_key(
+        &self,
+        index: usize,
+        status: DiscordStatus,
+        activity: Option<&DiscordActivityContext>,
+    ) -> Option<String> {
+        let (statuses, pattern, steam_app_ids) = match &self.matcher {
+            Matcher::Status => return Some(format!("status:{status}")),
+            Matcher::SteamOnly => {
+                return activity
+                    .and_then(|activity| activity.steam_app_id)
+                    .map(|app_id| format!("steam:{app_id}:{status}"));
+            }
+            Matcher::Rule {
+                statuses,
+                activity,
+                steam_app_ids,
+            } => (statuses, activity, steam_app_ids),
+        };
+
+        if !statuses.is_empty() && !statuses.contains(&status) {
+            return None;
+        }
+        let mut key = format!("rule:{index}");
+        if let Some(pattern) = pattern {
+            let activity = activity.filter(|activity| pattern.is_match(&activity.name))?;
+            key.push_str(&format!(":activity:{}", activity.name));
+        }
+        if !steam_app_ids.is_empty() {
+            let app_id = activity
+                .and_then(|activity| activity.steam_app_id)
+                .filter(|app_id| steam_app_ids.contains(app_id))?;
+            key.push_str(&format!(":steam:{app_id}"));
+        }
+        // Activity rules keep going when only the status changes.
+        if !statuses.is_empty() || !self.follows_activity() {
+            key.push_str(&format!(":status:{status}"));
+        }
+        Some(key)
+    }
+
+    fn follows_activity(&self) -> bool {
+        match &self.matcher {
+            Matcher::Status => false,
+            Matcher::SteamOnly => true,
+            Matcher::Rule {
+                activity,
+                steam_app_ids,
+                ..
+            } => activity.is_some() || !steam_app_ids.is_empty(),
+        }
+    }
+
+    /// Anchors that follow an activity start when Discord says the activity
+    /// started; this keeps reminders right after a restart or when monitoring
+    /// began mid-session. Status anchors start at `now`.
+    pub fn anchor_started_at(
+        &self,
+        activity: Option<&DiscordActivityContext>,
+        now: DateTime<Utc>,
+    ) -> DateTime<Utc> {
+        if !self.follows_activity() {
+            return now;
+        }
+        activity
+            .and_then(|activity| activity.started_at)
+            .filter(|started_at| *started_at <= now)
+            .unwrap_or(now)
+    }
+
+    /// Number of reminders due once the anchor has lasted `elapsed_seconds`,
+    /// honoring `max_count` and `max_duration_minutes`.
+    pub fn due_count(&self, elapsed_seconds: u64) -> u64 {
+        let elapsed = self
+            .max_duration_seconds
+            .map_or(elapsed_seconds, |max| elapsed_seconds.min(max));
+        let cap = |count: u64| self.max_count.map_or(count, |max| count.min(max));
+        let Some((repeat, escalation)) = self.intervals.split_last() else {
+            return 0;
+        };
+
+        let mut offset = 0u64;
+        for (count, gap) in escalation.iter().enumerate() {
+            offset = offset.saturating_add(*gap);
+            if offset > elapsed {
+                return cap(count as u64);
+            }
+        }
+        cap(escalation.len() as u64 + (elapsed - offset) / repeat)
+    }
+
+    /// Gap before reminder number `sequence` (1-based).
+    pub fn interval_seconds(&self, sequence: u64) -> u64 {
+        let index = (sequence.saturating_sub(1) as usize).min(self.intervals.len() - 1);
+        self.intervals[index]
+    }
+
+    /// Whether an anchor that lasted `elapsed_seconds` gets a `reminder_ended`
+    /// event; states that ended before the first reminder was due do not.
+    pub fn reports_end(&self, elapsed_seconds: u64) -> bool {
+        self.emit_on_end && elapsed_seconds >= self.intervals[0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+
+    use super::*;
+    use crate::config::ReminderRuleSettings;
+
+    fn dota(started_at: Option<DateTime<Utc>>) -> DiscordActivityContext {
+        DiscordActivityContext {
+            name: "Dota 2".to_string(),
+            details: None,
+            state: None,
+            steam_app_id: Some(570),
+            started_at,
+            ends_at: None,
+        }
+    }
+
+    fn rules(raw: &str) -> Vec<ReminderPolicy> {
+        #[derive(serde::Deserialize)]
+        struct Raw {
+            reminder: ReminderSettings,
+        }
+        let parsed: Raw = toml::from_str(raw).expect("reminder should parse");
+        build_policies(&parsed.reminder).expect("reminder rules should compile")
+    }
+
+    #[test]
+    fn reminder_anchor_key_steam_only() {
+        let settings = ReminderSettings {
+            enabled: true,
+            interval_minutes: 30,
+            steam_only: true,
+            check_interval_seconds: 30,
+            rules: Vec::new(),
+        };
+        let policies = build_policies(&settings).expect("policies should build");
+        let key = select_policy(&policies, false, DiscordStatus::Online, Some(&dota(None)));
+        assert_eq!(key, Some((0, "steam:570:online".to_string())));
+        assert_eq!(
+            select_policy(&policies, false, DiscordStatus::Online, None),
+            None
+        );
+    }
+
+    #[test]
+    fn steam_reminder_anchor_uses_discord_start_time() {
+        let now = Utc::now();
+        let mut settings = ReminderSettings {
+            enabled: true,
+            interval_minutes: 30,
+            steam_only: true,
+            check_interval_seconds: 30,
+            rules: Vec::new(),
+        };
+        let activity = dota(Some(now - TimeDelta::hours(2)));
+        let policies = build_policies(&settings).expect("policies should build");
+        assert_eq!(
+            policies[0].anchor_started_at(Some(&activity), now),
+            now - TimeDelta::hours(2)
+        );
+
+        settings.steam_only = false;
+        let policies = build_policies(&settings).expect("policies should build");
+        assert_eq!(policies[0].anchor_started_at(Some(&activity), now), now);
+    }
+
+    #[test]
+    fn first_matching_rule_anchors_reminders() {
+        let policies = rules(
+            r#"
+            [reminder]
+            enabled = true
+
+            [[reminder.rules]]
+            name = "dota"
+            steam_app_ids = [570]
+            intervals_minutes = [30, 60, 120]
+
+            [[reminder.rules]]
+            statuses = ["dnd"]
+            emit_on_end = true
+            "#,
+        );
+
+        assert_eq!(
+            select_policy(&policies, false, DiscordStatus::Idle, Some(&dota(None))),
+            Some((0, "rule:0:steam:570".to_string()))
+        );
+        assert_eq!(
+            select_policy(&policies, false, DiscordStatus::Dnd, None),
+            Some((1, "rule:1:status:dnd".to_string()))
+        );
+        assert_eq!(
+            select_policy(&policies, false, DiscordStatus::Online, None),
+            None
+        );
+        assert_eq!(policies[1].name.as_deref(), Some("reminder.rules[1]"));
+        assert_eq!(policies[1].interval_seconds(1), 1800);
+    }
+
+    #[test]
+    fn escalating_intervals_with_limits() {
+        let rule = |max_count, max_duration_minutes| ReminderRuleSettings {
+            name: None,
+            statuses: Vec::new(),
+            activity: None,
+            steam_app_ids: Vec::new(),
+            intervals_minutes: vec![30, 60, 120],
+            max_count,
+            max_duration_minutes,
+            emit_on_end: true,
+        };
+        let policy = |rule| {
+            build_policies(&ReminderSettings {
+                enabled: true,
+                rules: vec![rule],
+                ..ReminderSettings::default()
+            })
+            .expect("policies should build")
+            .remove(0)
+        };
+        let minutes = |minutes: u64| minutes * 60;
+
+        let unlimited = policy(rule(None, None));
+        // Reminders at 30m, 90m, 210m, 330m, ...
+        assert_eq!(unlimited.due_count(minutes(29)), 0);
+        assert_eq!(unlimited.due_count(minutes(30)), 1);
+        assert_eq!(unlimited.due_count(minutes(89)), 1);
+        assert_eq!(unlimited.due_count(minutes(90)), 2);
+        assert_eq!(unlimited.due_count(minutes(210)), 3);
+        assert_eq!(unlimited.due_count(minutes(330)), 4);
+        assert_eq!(unlimited.interval_seconds(2), minutes(60));
+        assert_eq!(unlimited.interval_seconds(7), minutes(120));
+        assert!(!unlimited.reports_end(minutes(29)));
+        assert!(unlimited.reports_end(minutes(30)));
+
+        assert_eq!(policy(rule(Some(2), None)).due_count(minutes(600)), 2);
+        assert_eq!(policy(rule(None, Some(200))).due_count(minutes(600)), 2);
+    }
+}
@@ -0,0 +1,355 @@
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use serenity::all::{Activity, ClientStatus, GuildId, OnlineStatus, Presence};
+use tokio::fs::{File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{Mutex, mpsc};
+use tracing::warn;
+
+use super::tracker::{ManualClock, ObservedPresence};
+use super::{PresenceBoard, PresenceEventHandler, TargetMonitor};
+use crate::config::Settings;
+use crate::event::DiscordStatusChangedEvent;
+use crate::quiet_hours::QuietHours;
+
+/// One presence update as received from the gateway, a line of a `--record` file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresenceRecord {
+    pub at: DateTime<Utc>,
+    pub user_id: u64,
+    #[serde(default)]
+    pub guild_id: Option<u64>,
+    pub status: OnlineStatus,
+    #[serde(default)]
+    pub client_status: Option<ClientStatus>,
+    /// Raw gateway activities, including the custom status.
+    #[serde(default)]
+    pub activities: Vec<Activity>,
+}
+
+impl PresenceRecord {
+    pub(super) fn from_presence(
+        presence: &Presence,
+        guild_id: Option<GuildId>,
+        at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            at,
+            user_id: presence.user.id.get(),
+            guild_id: guild_id.map(GuildId::get),
+            status: presence.status,
+            client_status: presence.client_status.clone(),
+            activities: presence.activities.clone(),
+        }
+    }
+}
+
+/// Appends the presences of monitored targets to a JSON lines file.
+pub struct PresenceRecorder {
+    file: Mutex<File>,
+}
+
+impl PresenceRecorder {
+    pub async fn open(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+            .with_context(|| format!("failed to open presence record file {}", path.display()))?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+
+    pub(super) async fn record(&self, record: &PresenceRecord) {
+        let mut line = match serde_json::to_vec(record) {
+            Ok(line) => line,
+            Err(err) => {
+                warn!(error = ?err, "failed to serialize presence record");
+                return;
+            }
+        };
+        line.push(b'\n');
+
+        let mut file = self.file.lock().await;
+        if let Err(err) = async {
+            file.write_all(&line).await?;
+            file.flush().await
+        }
+        .await
+        {
+            warn!(error = ?err, "failed to write presence record");
+        }
+    }
+}
+
+/// Reads a record file. Records must be in chronological order.
+pub fn read_records(path: &Path) -> Result<Vec<PresenceRecord>> {
+    let raw = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read presence records from {}", path.display()))?;
+
+    let mut records: Vec<PresenceRecord> = Vec::new();
+    for (index, line) in raw.lines().enumerate() {
+        let line_number = index + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: PresenceRecord = serde_json::from_str(line)
+            .with_context(|| format!("invalid presence record on line {line_number}"))?;
+        if record.user_id == 0 || record.guild_id == Some(0) {
+            bail!("invalid presence record on line {line_number}: ids must be greater than 0");
+        }
+        if records.last().is_some_and(|last| record.at < last.at) {
+            bail!("presence record on line {line_number} is older than the line before it");
+        }
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Feeds recorded presences through the monitor of every configured target and
+/// returns the events it would have emitted, in order.
+///
+/// The reminder and debounce loops run on a virtual clock that starts at the
+/// first record: reminders are checked every `check_interval_seconds` and
+/// debounced changes every second, exactly as the live loops would, up to the
+/// last record or `until`. Quiet hours suppress reminders; the rest of the
+/// delivery pipeline (event kind filters, `[[rules]]`, quiet hours for other
+/// events) is not applied.
+pub async fn replay(
+    settings: &Settings,
+    records: &[PresenceRecord],
+    until: Option<DateTime<Utc>>,
+) -> Result<Vec<DiscordStatusChangedEvent>> {
+    let Some(start) = records.first().map(|record| record.at) else {
+        return Ok(Vec::new());
+    };
+    let clock = ManualClock::new(start);
+    let board = PresenceBoard::restore_with_clock(
+        &settings.discord,
+        &settings.reminder,
+        None,
+        Arc::new(clock.clone()),
+    )
+    .await?;
+    let quiet_hours =
+        QuietHours::new(&settings.quiet_hours).context("failed to setup quiet hours")?;
+
+    let (tx, mut rx) = mpsc::channel(16);
+    let handler = PresenceEventHandler {
+        targets: board.targets.to_vec(),
+        tx,
+        state_cache: None,
+        history: None,
+        recorder: None,
+        connected: AtomicBool::new(false),
+    };
+    let mut loops = VirtualLoops::new(&handler.targets, clock.clone(), start, quiet_hours);
+    let mut events = Vec::new();
+
+    for record in records {
+        loops.advance(record.at, &mut events).await;
+        clock.set(record.at);
+        let presence = ObservedPresence::from_gateway(
+            record.status,
+            record.client_status.as_ref(),
+            &record.activities,
+            record.at,
+        );
+        for target in handler
+            .targets
+            .iter()
+            .filter(|target| target.matches_presence(record.user_id, record.guild_id))
+        {
+            handler
+                .handle_presence_update(target, record.guild_id.map(GuildId::new), presence.clone())
+                .await;
+            while let Ok(event) = rx.try_recv() {
+                events.push(event);
+            }
+        }
+    }
+
+    let last = records.last().map_or(start, |record| record.at);
+    loops
+        .advance(until.unwrap_or(last).max(last), &mut events)
+        .await;
+    Ok(events)
+}
+
+/// Runs the per-target reminder and debounce loops on the manual clock, at the
+/// instants their tickers would fire, without waiting.
+struct VirtualLoops {
+    clock: ManualClock,
+    start: DateTime<Utc>,
+    quiet_hours: Option<QuietHours>,
+    targets: Vec<(Arc<TargetMonitor>, DateTime<Utc>)>,
+}
+
+enum Tick {
+    Reminder,
+    Debounce,
+}
+
+impl VirtualLoops {
+    fn new(
+        targets: &[Arc<TargetMonitor>],
+        clock: ManualClock,
+        start: DateTime<Utc>,
+        quiet_hours: Option<QuietHours>,
+    ) -> Self {
+        Self {
+            clock,
+            start,
+            quiet_hours,
+            // Like `tokio::time::interval`, the first reminder check is immediate.
+            targets: targets
+                .iter()
+                .map(|target| (target.clone(), start))
+                .collect(),
+        }
+    }
+
+    /// Runs every tick up to and including `until`.
+    async fn advance(&mut self, until: DateTime<Utc>, events: &mut Vec<DiscordStatusChangedEvent>) {
+        loop {
+            let mut next: Option<(DateTime<Utc>, usize, Tick)> = None;
+            for (index, (target, next_reminder_check)) in self.targets.iter().enumerate() {
+                let debounce_due = target
+                    .tracker
+                    .lock()
+                    .await
+                    .pending_due_at()
+                    .map(|due_at| self.next_second(due_at));
+                let candidates = [
+                    Some((*next_reminder_check, Tick::Reminder)),
+                    debounce_due.map(|at| (at, Tick::Debounce)),
+                ];
+                for (at, tick) in candidates.into_iter().flatten() {
+                    if next.as_ref().is_none_or(|(next_at, _, _)| at < *next_at) {
+                        next = Some((at, index, tick));
+                    }
+                }
+            }
+            let Some((at, index, tick)) = next.filter(|(at, _, _)| *at <= until) else {
+                return;
+            };
+
+            self.clock.set(at);
+            let (target, next_reminder_check) = &mut self.targets[index];
+            let mut tracker = target.tracker.lock().await;
+            match tick {
+                Tick::Reminder => {
+                    let check_interval_seconds =
+                        tracker.reminders().settings.check_interval_seconds;
+                    *next_reminder_check = at + TimeDelta::seconds(check_interval_seconds as i64);
+                    events.extend(tracker.tick_reminder(self.quiet_hours.as_ref()));
+                }
+                Tick::Debounce => events.extend(tracker.take_debounced()),
+            }
+        }
+    }
+
+    /// The debounce loop ticks every whole second after the start.
+    fn next_second(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        let millis = (at - self.start).num_milliseconds().max(0);
+        self.start + TimeDelta::seconds((millis + 999) / 1000)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::{DiscordStatus, EventKind};
+
+    const CONFIG: &str = r#"
+        [discord]
+        bot_token = "token"
+        user_id = 42
+        emit_on_activity_change = true
+
+        [webhook]
+        url = "http://127.0.0.1:18789/hooks/wake"
+
+        [reminder]
+        enabled = true
+        interval_minutes = 30
+    "#;
+
+    fn record(line: &str) -> PresenceRecord {
+        serde_json::from_str(line).expect("record should parse")
+    }
+
+    #[tokio::test]
+    async fn replay_emits_changes_and_reminders_on_virtual_clock() {
+        let records = [
+            record(r#"{"at":"2026-02-13T10:00:00Z","user_id":42,"status":"online"}"#),
+            record(
+                r#"{"at":"2026-02-13T10:01:00Z","user_id":42,"status":"online",
+                "activities":[{"type":0,"name":"Dota 2","created_at":0}]}"#,
+            ),
+            record(r#"{"at":"2026-02-13T10:02:00Z","user_id":7,"status":"idle"}"#),
+        ];
+        let at = |raw: &str| {
+            raw.parse::<DateTime<Utc>>()
+                .expect("timestamp should parse")
+        };
+
+        let settings: Settings = toml::from_str(CONFIG).expect("config should parse");
+        let events = replay(&settings, &records, Some(at("2026-02-13T11:00:00Z")))
+            .await
+            .expect("replay should succeed");
+
+        let summary: Vec<_> = events
+            .iter()
+            .map(|event| (event.kind(), event.observed_at))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (EventKind::ActivityChange, at("2026-02-13T10:01:00Z")),
+                (EventKind::Reminder, at("2026-02-13T10:30:00Z")),
+                (EventKind::Reminder, at("2026-02-13T11:00:00Z")),
+            ]
+        );
+        assert_eq!(events[1].current_status, DiscordStatus::Online);
+        assert_eq!(
+            events[1]
+                .activity
+                .as_ref()
+                .map(|activity| activity.name.as_str()),
+            Some("Dota 2")
+        );
+    }
+
+    #[test]
+    fn records_round_trip_and_must_be_ordered() {
+        let path =
+            std::env::temp_dir().join(format!("statushub-records-{}.jsonl", std::process::id()));
+        let later = record(
+            r#"{"at":"2026-02-13T10:05:00Z","user_id":42,"guild_id":1,"status":"dnd","client_status":{"desktop":"dnd"}}"#,
+        );
+        let earlier = record(r#"{"at":"2026-02-13T10:00:00Z","user_id":42,"status":"online"}"#);
+        let lines = [&later, &earlier]
+            .map(|record| serde_json::to_string(record).expect("record should serialize"))
+            .join("\n");
+
+        std::fs::write(&path, &lines).expect("records should be written");
+        let error = read_records(&path).expect_err("unordered records should be rejected");
+        assert!(error.to_string().contains("line 2"));
+
+        std::fs::write(&path, lines.lines().next().unwrap_or_default())
+            .expect("records should be written");
+        let records = read_records(&path).expect("records should load");
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].status, OnlineStatus::DoNotDisturb);
+        assert_eq!(records[0].guild_id, Some(1));
+    }
+}
@@ -0,0 +1,1137 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicI64, Ordering};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use tracing::{debug, info};
+
+use super::debounce::{self, DebouncePolicy, PendingChange};
+use super::reminder::{self, ReminderPolicy, Reminders};
+use super::{ReminderAnchorSnapshot, TargetStatusSnapshot};
+use crate::config::DiscordTargetSettings;
+use crate::event::{
+    DiscordActivity, DiscordActivityContext, DiscordClientStatus, DiscordCustomStatus,
+    DiscordStatus, DiscordStatusChangedEvent, MusicContext, ReminderContext,
+};
+use crate::quiet_hours::QuietHours;
+
+/// Source of the current time for a [`PresenceTracker`].
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The wall clock.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that only moves when set, used by replay and tests.
+#[derive(Clone)]
+pub struct ManualClock(Arc<AtomicI64>);
+
+impl ManualClock {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self(Arc::new(AtomicI64::new(now.timestamp_micros())))
+    }
+
+    pub fn set(&self, now: DateTime<Utc>) {
+        self.0.store(now.timestamp_micros(), Ordering::Relaxed);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp_micros(self.0.load(Ordering::Relaxed)).unwrap_or_default()
+    }
+}
+
+/// A presence normalized into the parts the tracker compares and emits. The
+/// serenity adapter builds it from gateway presences.
+#[derive(Debug, Clone)]
+pub(super) struct ObservedPresence {
+    pub status: DiscordStatus,
+    pub client_status: Option<DiscordClientStatus>,
+    pub custom_status: Option<DiscordCustomStatus>,
+    pub activity: Option<DiscordActivityContext>,
+    pub activities: Vec<DiscordActivity>,
+    pub music: Option<MusicContext>,
+    /// Identifies the activity set; any difference counts as an activity change.
+    pub activity_fingerprint: String,
+    /// Fingerprint with the Spotify activity reduced to its name, for targets that
+    /// report track changes separately.
+    pub activity_fingerprint_without_track: String,
+}
+
+/// What observing one presence produced.
+pub(super) struct PresenceUpdate {
+    /// Events to emit, in order.
+    pub events: Vec<DiscordStatusChangedEvent>,
+    pub status_changed: bool,
+    pub observed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+struct RuntimePresenceState {
+    current_status: Option<DiscordStatus>,
+    current_client_status: Option<DiscordClientStatus>,
+    current_custom_status: Option<DiscordCustomStatus>,
+    current_guild_id: Option<u64>,
+    current_activity: Option<DiscordActivityContext>,
+    current_activities: Vec<DiscordActivity>,
+    /// Activities as of the last emitted change, which `activities_started` and
+    /// `activities_ended` are relative to.
+    reported_activities: Vec<DiscordActivity>,
+    current_activity_fingerprint: Option<String>,
+    current_music: Option<MusicContext>,
+    /// Music as of the last emitted change; `previous_music` of the next
+    /// `track_change`, so tracks dropped by the rate limit are skipped.
+    reported_music: Option<MusicContext>,
+    last_track_change_at: Option<DateTime<Utc>>,
+    pending_change: Option<PendingChange>,
+    reminders: Arc<Reminders>,
+    reminder_anchor: Option<ReminderAnchor>,
+    observed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone)]
+struct ReminderAnchor {
+    key: String,
+    /// Index into the target's reminder policies.
+    policy: usize,
+    status: DiscordStatus,
+    activity: Option<DiscordActivityContext>,
+    started_at_unix: i64,
+    last_sequence: u64,
+}
+
+/// The presence state machine of one target: decides which status, activity,
+/// platform, custom status, track, reminder and reminder-ended events a sequence
+/// of presences produces. It performs no I/O and reads time only from its
+/// [`Clock`].
+pub(super) struct PresenceTracker {
+    settings: DiscordTargetSettings,
+    debounce: DebouncePolicy,
+    clock: Arc<dyn Clock>,
+    state: RuntimePresenceState,
+}
+
+impl PresenceTracker {
+    /// Starts from `initial_status`, typically restored from the state cache.
+    pub fn new(
+        settings: DiscordTargetSettings,
+        reminders: Reminders,
+        initial_status: Option<DiscordStatus>,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        let now = clock.now();
+        let reminder_anchor = initial_status.and_then(|status| {
+            reminder::select_policy(
+                &reminders.policies,
+                settings.rich_presence_only,
+                status,
+                None,
+            )
+            .map(|(policy, key)| {
+                ReminderAnchor::start(key, &reminders.policies[policy], policy, status, None, now)
+            })
+        });
+
+        Self {
+            debounce: DebouncePolicy {
+                status_window: TimeDelta::seconds(settings.status_debounce_seconds as i64),
+                activity_window: TimeDelta::seconds(settings.activity_debounce_seconds as i64),
+                emit_on_activity_change: settings.emit_on_activity_change,
+            },
+            settings,
+            clock,
+            state: RuntimePresenceState {
+                current_status: initial_status,
+                current_client_status: None,
+                current_custom_status: None,
+                current_guild_id: None,
+                current_activity: None,
+                current_activities: Vec::new(),
+                reported_activities: Vec::new(),
+                current_activity_fingerprint: None,
+                current_music: None,
+                reported_music: None,
+                last_track_change_at: None,
+                pending_change: None,
+                reminders: Arc::new(reminders),
+                reminder_anchor,
+                observed_at: None,
+            },
+        }
+    }
+
+    pub fn settings(&self) -> &DiscordTargetSettings {
+        &self.settings
+    }
+
+    pub fn reminders(&self) -> &Reminders {
+        &self.state.reminders
+    }
+
+    pub fn debounce_enabled(&self) -> bool {
+        self.debounce.is_enabled()
+    }
+
+    /// When the change held back by debounce becomes due.
+    pub fn pending_due_at(&self) -> Option<DateTime<Utc>> {
+        self.state
+            .pending_change
+            .as_ref()
+            .map(|change| change.due_at)
+    }
+
+    /// Applies a presence observed now, from `guild_id`. The replaced reminder
+    /// anchor's `reminder_ended` event comes before the change itself, which is
+    /// left out when it is the initial status or held back by debounce; while a
+    /// change is held back, the reminder anchor stays where it was.
+    pub fn observe(&mut self, guild_id: Option<u64>, presence: ObservedPresence) -> PresenceUpdate {
+        let ObservedPresence {
+            status: next_status,
+            client_status,
+            custom_status,
+            activity,
+            activities,
+            music,
+            activity_fingerprint,
+            activity_fingerprint_without_track,
+        } = presence;
+        let settings = &self.settings;
+        let state = &mut self.state;
+        let activity_fingerprint = if settings.emit_on_track_change {
+            activity_fingerprint_without_track
+        } else {
+            activity_fingerprint
+        };
+        let now = self.clock.now();
+        let has_activity = !activity_fingerprint.is_empty();
+
+        let previous = state.current_status;
+        let status_changed = previous != Some(next_status);
+        let activity_changed = state
+            .current_activity_fingerprint
+            .as_ref()
+            .map(|v| v != &activity_fingerprint)
+            .unwrap_or(!activity_fingerprint.is_empty());
+        // Platform and custom status changes only make their own event kinds on their
+        // own; together with a status or activity change they are reported as that.
+        let standalone_change = previous.is_some()
+            && !status_changed
+            && !(settings.emit_on_activity_change && activity_changed);
+        // Only the status survives a restart; platforms and custom status are unknown
+        // until the first presence after it, which must not count as a change.
+        let observed_before = state.observed_at.is_some();
+        let platform_triggered = settings.emit_on_platform_change
+            && !settings.rich_presence_only
+            && standalone_change
+            && observed_before
+            && state.current_client_status != client_status;
+        let custom_status_triggered = settings.emit_on_custom_status_change
+            && !settings.rich_presence_only
+            && standalone_change
+            && observed_before
+            && state.current_custom_status != custom_status;
+        let track_baseline = state
+            .reported_music
+            .clone()
+            .or_else(|| state.current_music.clone());
+        let track_changed = match (&state.current_music, &music) {
+            (Some(previous), Some(current)) => {
+                !previous.is_same_track(current)
+                    && track_baseline
+                        .as_ref()
+                        .is_none_or(|baseline| !baseline.is_same_track(current))
+            }
+            _ => false,
+        };
+        let track_rate_limited = state.last_track_change_at.is_some_and(|at| {
+            now - at < TimeDelta::seconds(settings.track_change_min_interval_seconds as i64)
+        });
+        let track_triggered = settings.emit_on_track_change && standalone_change && track_changed;
+        if track_triggered && track_rate_limited {
+            debug!(
+                user_id = settings.user_id,
+                "track change within track_change_min_interval_seconds, not emitting"
+            );
+        }
+        let track_triggered = track_triggered && !track_rate_limited;
+        if track_triggered {
+            state.last_track_change_at = Some(now);
+        }
+
+        state.current_status = Some(next_status);
+        let previous_client_status =
+            std::mem::replace(&mut state.current_client_status, client_status);
+        let previous_custom_status =
+            std::mem::replace(&mut state.current_custom_status, custom_status.clone());
+        state.current_guild_id = guild_id;
+        state.current_activity = activity.clone();
+        state.current_activities = activities.clone();
+        let previous_fingerprint = state
+            .current_activity_fingerprint
+            .replace(activity_fingerprint.clone());
+        state.current_music = music.clone();
+        state.observed_at = Some(now);
+
+        let should_emit = should_emit_presence_event(
+            status_changed || platform_triggered,
+            activity_changed,
+            settings.emit_on_activity_change,
+            settings.rich_presence_only,
+            has_activity,
+            settings.emit_initial_status,
+            previous.is_some(),
+        ) || custom_status_triggered
+            || track_triggered;
+
+        let mut events = Vec::new();
+        if previous.is_none() && !settings.emit_initial_status {
+            info!(
+                user_id = settings.user_id,
+                status = %next_status,
+                "captured initial status without emitting"
+            );
+            state.reported_activities = activities;
+            state.reported_music = music;
+        } else if should_emit || state.pending_change.is_some() {
+            let event = DiscordStatusChangedEvent::new(
+                settings.user_id,
+                guild_id,
+                previous,
+                next_status,
+                activity,
+                None,
+            )
+            .with_client_status(
+                client_status,
+                platform_triggered.then(|| previous_client_status.unwrap_or_default()),
+            )
+            .with_custom_status(
+                custom_status,
+                custom_status_triggered.then(|| previous_custom_status.unwrap_or_default()),
+            )
+            .with_music(music, track_baseline.filter(|_| track_triggered))
+            .with_activities(activities, &state.reported_activities)
+            .with_observed_at(now);
+
+            if !should_emit {
+                // Not a change of its own, but a held-back change is released with
+                // the presence as of its release rather than as of when it started.
+                debounce::refresh(&mut state.pending_change, event);
+            } else {
+                let event = if previous.is_some() && self.debounce.is_enabled() {
+                    debounce::debounce(
+                        &mut state.pending_change,
+                        &self.debounce,
+                        event,
+                        &state.reported_activities,
+                        previous_fingerprint,
+                        &activity_fingerprint,
+                        now,
+                    )
+                } else {
+                    Some(event)
+                };
+                match event {
+                    Some(event) => {
+                        state.reported_activities = event.activities.clone();
+                        state.reported_music = event.music.clone();
+                        events.push(event);
+                    }
+                    None => debug!(
+                        user_id = settings.user_id,
+                        "presence change held back by debounce"
+                    ),
+                }
+            }
+        }
+
+        // A change held back by debounce may still turn out to be a flap, so the
+        // reminder anchor only follows once nothing is pending.
+        if self.state.pending_change.is_none()
+            && let Some(ended) = self.follow_reminder_anchor(now)
+        {
+            events.insert(0, ended);
+        }
+
+        PresenceUpdate {
+            events,
+            status_changed,
+            observed_at: now,
+        }
+    }
+
+    /// Fires the next reminder when one is due now. During quiet hours that
+    /// suppress reminders, or while a change is held back by debounce, nothing
+    /// fires; the next reminder after that reports the full elapsed time.
+    pub fn tick_reminder(
+        &mut self,
+        quiet_hours: Option<&QuietHours>,
+    ) -> Option<DiscordStatusChangedEvent> {
+        let now = self.clock.now();
+        if quiet_hours.is_some_and(|quiet_hours| quiet_hours.suppresses_reminders(now)) {
+            return None;
+        }
+        let state = &mut self.state;
+        if state.pending_change.is_some() {
+            return None;
+        }
+        let current_status = state.current_status?;
+        let anchor = state.reminder_anchor.as_mut()?;
+        let policy = &state.reminders.policies[anchor.policy];
+        let elapsed = anchor.elapsed_seconds(now);
+        let sequence = policy.due_count(elapsed);
+        if sequence == 0 || sequence <= anchor.last_sequence {
+            return None;
+        }
+        anchor.last_sequence = sequence;
+        let activity = state.current_activity.clone();
+        let activity_elapsed_seconds = activity
+            .as_ref()
+            .and_then(|activity| activity.elapsed_seconds(now));
+        Some(
+            DiscordStatusChangedEvent::new(
+                self.settings.user_id,
+                state.current_guild_id,
+                None,
+                current_status,
+                activity,
+                Some(ReminderContext {
+                    elapsed_seconds: elapsed,
+                    interval_seconds: policy.interval_seconds(sequence),
+                    sequence,
+                    activity_elapsed_seconds,
+                    rule: policy.name.clone(),
+                    ended: false,
+                }),
+            )
+            .with_client_status(state.current_client_status, None)
+            .with_custom_status(state.current_custom_status.clone(), None)
+            .with_music(state.current_music.clone().map(|music| music.at(now)), None)
+            .with_activities(state.current_activities.clone(), &state.current_activities)
+            .with_observed_at(now),
+        )
+    }
+
+    /// Takes the change held back by debounce once it has been stable long enough,
+    /// preceded by the `reminder_ended` event of the anchor it replaces. The new
+    /// anchor starts when the change was observed.
+    pub fn take_debounced(&mut self) -> Vec<DiscordStatusChangedEvent> {
+        let Some(event) = debounce::take_due(&mut self.state.pending_change, self.clock.now())
+        else {
+            return Vec::new();
+        };
+        self.state.reported_activities = event.activities.clone();
+        self.state.reported_music = event.music.clone();
+        let mut events: Vec<_> = self
+            .follow_reminder_anchor(event.observed_at)
+            .into_iter()
+            .collect();
+        events.push(event);
+        events
+    }
+
+    /// Moves the reminder anchor to the current status and activity when its key
+    /// changed, starting the new one at `at`. Returns the `reminder_ended` event of
+    /// the replaced anchor when its policy reports it.
+    fn follow_reminder_anchor(&mut self, at: DateTime<Utc>) -> Option<DiscordStatusChangedEvent> {
+        let state = &mut self.state;
+        let current_status = state.current_status?;
+        let reminders = state.reminders.clone();
+        let next_anchor = reminder::select_policy(
+            &reminders.policies,
+            self.settings.rich_presence_only,
+            current_status,
+            state.current_activity.as_ref(),
+        );
+        let current_anchor_key = state
+            .reminder_anchor
+            .as_ref()
+            .map(|anchor| anchor.key.as_str());
+        if current_anchor_key == next_anchor.as_ref().map(|(_, key)| key.as_str()) {
+            return None;
+        }
+        let next_anchor = next_anchor.map(|(policy, key)| {
+            ReminderAnchor::start(
+                key,
+                &reminders.policies[policy],
+                policy,
+                current_status,
+                state.current_activity.clone(),
+                at,
+            )
+        });
+        let ended = std::mem::replace(&mut state.reminder_anchor, next_anchor)?;
+        let policy = &reminders.policies[ended.policy];
+        policy.reports_end(ended.elapsed_seconds(at)).then(|| {
+            build_reminder_ended_event(
+                self.settings.user_id,
+                state.current_guild_id,
+                policy,
+                ended,
+                current_status,
+                at,
+            )
+        })
+    }
+
+    /// Swaps in reloaded reminder settings. The running anchor keeps counting when
+    /// its key is unchanged; otherwise reminders restart under the new rules.
+    pub fn reload_reminders(&mut self, reminders: Reminders) {
+        let state = &mut self.state;
+        state.reminders = Arc::new(reminders);
+        let next_anchor = state.current_status.and_then(|status| {
+            reminder::select_policy(
+                &state.reminders.policies,
+                self.settings.rich_presence_only,
+                status,
+                state.current_activity.as_ref(),
+            )
+        });
+        state.reminder_anchor = match (state.reminder_anchor.take(), next_anchor) {
+            (Some(mut anchor), Some((policy, key))) if anchor.key == key => {
+                anchor.policy = policy;
+                Some(anchor)
+            }
+            (_, Some((policy, key))) => Some(ReminderAnchor::start(
+                key,
+                &state.reminders.policies[policy],
+                policy,
+                state.current_status.unwrap_or(DiscordStatus::Unknown),
+                state.current_activity.clone(),
+                self.clock.now(),
+            )),
+            (_, None) => None,
+        };
+    }
+
+    pub fn snapshot(&self) -> TargetStatusSnapshot {
+        let state = &self.state;
+        TargetStatusSnapshot {
+            user_id: self.settings.user_id,
+            guild_id: self.settings.guild_id,
+            current_guild_id: state.current_guild_id,
+            status: state.current_status,
+            client_status: state.current_client_status,
+            custom_status: state.current_custom_status.clone(),
+            steam_app_id: state
+                .current_activity
+                .as_ref()
+                .and_then(|activity| activity.steam_app_id),
+            activity: state.current_activity.clone(),
+            activities: state.current_activities.clone(),
+            music: state
+                .current_music
+                .clone()
+                .map(|music| music.at(self.clock.now())),
+            reminder_anchor: state
+                .reminder_anchor
+                .as_ref()
+                .map(|anchor| ReminderAnchorSnapshot {
+                    key: anchor.key.clone(),
+                    rule: state.reminders.policies[anchor.policy].name.clone(),
+                    started_at: DateTime::from_timestamp(anchor.started_at_unix, 0),
+                    last_sequence: anchor.last_sequence,
+                }),
+            observed_at: state.observed_at,
+        }
+    }
+}
+
+impl ReminderAnchor {
+    fn start(
+        key: String,
+        policy: &ReminderPolicy,
+        policy_index: usize,
+        status: DiscordStatus,
+        activity: Option<DiscordActivityContext>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let started_at = policy.anchor_started_at(activity.as_ref(), now);
+        Self {
+            key,
+            policy: policy_index,
+            status,
+            activity,
+            started_at_unix: started_at.timestamp(),
+            last_sequence: 0,
+        }
+    }
+
+    fn elapsed_seconds(&self, now: DateTime<Utc>) -> u64 {
+        now.timestamp().saturating_sub(self.started_at_unix).max(0) as u64
+    }
+}
+
+/// Reports that the state a reminder anchor followed has ended. The event carries
+/// the ended activity and status, with `current_status` being the new status.
+fn build_reminder_ended_event(
+    user_id: u64,
+    guild_id: Option<u64>,
+    policy: &ReminderPolicy,
+    anchor: ReminderAnchor,
+    current_status: DiscordStatus,
+    now: DateTime<Utc>,
+) -> DiscordStatusChangedEvent {
+    let elapsed = anchor.elapsed_seconds(now);
+    let activity_elapsed_seconds = anchor
+        .activity
+        .as_ref()
+        .and_then(|activity| activity.elapsed_seconds(now));
+    DiscordStatusChangedEvent::new(
+        user_id,
+        guild_id,
+        Some(anchor.status),
+        current_status,
+        anchor.activity,
+        Some(ReminderContext {
+            elapsed_seconds: elapsed,
+            interval_seconds: policy.interval_seconds(anchor.last_sequence + 1),
+            sequence: anchor.last_sequence,
+            activity_elapsed_seconds,
+            rule: policy.name.clone(),
+            ended: true,
+        }),
+    )
+    .with_observed_at(now)
+}
+
+fn should_emit_presence_event(
+    status_changed: bool,
+    activity_changed: bool,
+    emit_on_activity_change: bool,
+    rich_presence_only: bool,
+    has_activity: bool,
+    emit_initial_status: bool,
+    has_previous_status: bool,
+) -> bool {
+    let trigger_change = if rich_presence_only {
+        emit_on_activity_change && activity_changed && has_activity
+    } else {
+        status_changed || (emit_on_activity_change && activity_changed)
+    };
+    trigger_change && (emit_initial_status || has_previous_status)
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::config::ReminderSettings;
+    use crate::event::{ActivityKind, EventKind};
+
+    const STATUSES: [DiscordStatus; 4] = [
+        DiscordStatus::Online,
+        DiscordStatus::Idle,
+        DiscordStatus::Dnd,
+        DiscordStatus::Offline,
+    ];
+    const GAMES: [&str; 3] = ["Dota 2", "Hades", "Factorio"];
+
+    fn start() -> DateTime<Utc> {
+        DateTime::from_timestamp(1_770_976_800, 0).expect("timestamp should be valid")
+    }
+
+    fn target(extra: &str) -> DiscordTargetSettings {
+        toml::from_str(&format!("user_id = 42\n{extra}")).expect("target should parse")
+    }
+
+    fn reminders() -> Reminders {
+        Reminders::new(ReminderSettings {
+            enabled: true,
+            interval_minutes: 30,
+            ..ReminderSettings::default()
+        })
+        .expect("reminders should compile")
+    }
+
+    fn tracker(settings: DiscordTargetSettings, clock: &ManualClock) -> PresenceTracker {
+        PresenceTracker::new(settings, reminders(), None, Arc::new(clock.clone()))
+    }
+
+    fn presence(status: DiscordStatus, game: Option<&str>) -> ObservedPresence {
+        let fingerprint = game.unwrap_or_default().to_string();
+        ObservedPresence {
+            status,
+            client_status: None,
+            custom_status: None,
+            activity: game.map(|name| DiscordActivityContext {
+                name: name.to_string(),
+                details: None,
+                state: None,
+                steam_app_id: None,
+                started_at: None,
+                ends_at: None,
+            }),
+            activities: game
+                .map(|name| DiscordActivity {
+                    kind: ActivityKind::Playing,
+                    name: name.to_string(),
+                    details: None,
+                    state: None,
+                    application_id: None,
+                    steam_app_id: None,
+                    url: None,
+                    timestamps: None,
+                    assets: None,
+                    buttons: Vec::new(),
+                })
+                .into_iter()
+                .collect(),
+            music: None,
+            activity_fingerprint_without_track: fingerprint.clone(),
+            activity_fingerprint: fingerprint,
+        }
+    }
+
+    /// Presences as (status, game, seconds since the previous one).
+    fn presences() -> impl Strategy<Value = Vec<(usize, Option<usize>, i64)>> {
+        prop::collection::vec(
+            (
+                0..STATUSES.len(),
+                prop::option::of(0..GAMES.len()),
+                0..3_600i64,
+            ),
+            1..40,
+        )
+    }
+
+    #[test]
+    fn emit_on_activity_change_when_enabled() {
+        assert!(should_emit_presence_event(
+            false, true, true, false, true, false, true
+        ));
+        assert!(!should_emit_presence_event(
+            false, true, false, false, true, false, true
+        ));
+    }
+
+    #[test]
+    fn rich_presence_only_blocks_status_only_events() {
+        assert!(!should_emit_presence_event(
+            true, false, true, true, false, false, true
+        ));
+        assert!(should_emit_presence_event(
+            false, true, true, true, true, false, true
+        ));
+    }
+
+    #[test]
+    fn reminders_follow_the_clock() {
+        let clock = ManualClock::new(start());
+        let mut tracker = tracker(target(""), &clock);
+        assert!(
+            tracker
+                .observe(None, presence(DiscordStatus::Online, None))
+                .events
+                .is_empty()
+        );
+
+        clock.set(start() + TimeDelta::minutes(29));
+        assert!(tracker.tick_reminder(None).is_none());
+
+        clock.set(start() + TimeDelta::minutes(61));
+        let reminder = tracker.tick_reminder(None).expect("reminder should be due");
+        assert_eq!(reminder.kind(), EventKind::Reminder);
+        assert_eq!(reminder.observed_at, start() + TimeDelta::minutes(61));
+        let context = reminder.reminder.expect("reminder context should be set");
+        assert_eq!((context.sequence, context.elapsed_seconds), (2, 3_660));
+        assert!(tracker.tick_reminder(None).is_none());
+
+        let update = tracker.observe(None, presence(DiscordStatus::Idle, None));
+        assert!(update.status_changed);
+        assert_eq!(
+            update.events[0].previous_status,
+            Some(DiscordStatus::Online)
+        );
+        clock.set(start() + TimeDelta::minutes(89));
+        assert!(tracker.tick_reminder(None).is_none());
+    }
+
+    #[test]
+    fn debounced_change_waits_for_its_window() {
+        let clock = ManualClock::new(start());
+        let mut tracker = tracker(target("status_debounce_seconds = 60"), &clock);
+        tracker.observe(None, presence(DiscordStatus::Online, None));
+
+        clock.set(start() + TimeDelta::seconds(10));
+        assert!(
+            tracker
+                .observe(None, presence(DiscordStatus::Idle, None))
+                .events
+                .is_empty()
+        );
+        assert_eq!(
+            tracker.pending_due_at(),
+            Some(start() + TimeDelta::seconds(70))
+        );
+
+        clock.set(start() + TimeDelta::seconds(69));
+        assert!(tracker.take_debounced().is_empty());
+        clock.set(start() + TimeDelta::seconds(70));
+        let events = tracker.take_debounced();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].previous_status, Some(DiscordStatus::Online));
+        assert_eq!(events[0].current_status, DiscordStatus::Idle);
+        assert!(tracker.pending_due_at().is_none());
+    }
+
+    #[test]
+    fn debounced_change_is_released_with_the_latest_activity() {
+        let clock = ManualClock::new(start());
+        let mut tracker = tracker(
+            target("status_debounce_seconds = 60\nemit_on_activity_change = false"),
+            &clock,
+        );
+        tracker.observe(None, presence(DiscordStatus::Online, Some("Factorio")));
+
+        clock.set(start() + TimeDelta::seconds(10));
+        tracker.observe(None, presence(DiscordStatus::Idle, Some("Factorio")));
+        clock.set(start() + TimeDelta::seconds(20));
+        assert!(
+            tracker
+                .observe(None, presence(DiscordStatus::Idle, Some("Celeste")))
+                .events
+                .is_empty()
+        );
+        assert_eq!(
+            tracker.pending_due_at(),
+            Some(start() + TimeDelta::seconds(70))
+        );
+
+        clock.set(start() + TimeDelta::seconds(70));
+        let events = tracker.take_debounced();
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event.previous_status, Some(DiscordStatus::Online));
+        assert_eq!(event.current_status, DiscordStatus::Idle);
+        assert_eq!(
+            event
+                .activity
+                .as_ref()
+                .map(|activity| activity.name.as_str()),
+            Some("Celeste")
+        );
+        assert_eq!(event.activities.len(), 1);
+        assert_eq!(event.activities[0].name, "Celeste");
+        assert_eq!(event.activities_started[0].name, "Celeste");
+        assert_eq!(event.activities_ended[0].name, "Factorio");
+        assert_eq!(event.observed_at, start() + TimeDelta::seconds(10));
+    }
+
+    #[test]
+    fn debounced_flap_keeps_the_reminder_anchor() {
+        let clock = ManualClock::new(start());
+        let reminders = Reminders::new(
+            toml::from_str(
+                r#"
+                enabled = true
+
+                [[rules]]
+                statuses = ["online"]
+                intervals_minutes = [30]
+                emit_on_end = true
+                "#,
+            )
+            .expect("reminder should parse"),
+        )
+        .expect("reminders should compile");
+        let mut tracker = PresenceTracker::new(
+            target("status_debounce_seconds = 60"),
+            reminders,
+            None,
+            Arc::new(clock.clone()),
+        );
+        let at = |minutes: i64| start() + TimeDelta::minutes(minutes);
+        tracker.observe(None, presence(DiscordStatus::Online, None));
+
+        clock.set(at(40));
+        assert!(tracker.tick_reminder(None).is_some());
+        clock.set(at(41));
+        let update = tracker.observe(None, presence(DiscordStatus::Idle, None));
+        assert!(update.events.is_empty());
+        assert!(tracker.tick_reminder(None).is_none());
+        clock.set(at(41) + TimeDelta::seconds(30));
+        let update = tracker.observe(None, presence(DiscordStatus::Online, None));
+        assert!(update.events.is_empty());
+        assert!(tracker.take_debounced().is_empty());
+
+        clock.set(at(60));
+        let reminder = tracker.tick_reminder(None).expect("reminder should be due");
+        assert_eq!(reminder.reminder.map(|context| context.sequence), Some(2));
+
+        clock.set(at(61));
+        tracker.observe(None, presence(DiscordStatus::Idle, None));
+        clock.set(at(62));
+        let kinds: Vec<_> = tracker
+            .take_debounced()
+            .iter()
+            .map(DiscordStatusChangedEvent::kind)
+            .collect();
+        assert_eq!(kinds, [EventKind::ReminderEnded, EventKind::StatusChange]);
+    }
+
+    #[test]
+    fn activity_diff_is_relative_to_the_last_emitted_event() {
+        let clock = ManualClock::new(start());
+        let mut tracker = tracker(target("emit_on_activity_change = false"), &clock);
+        let names = |activities: &[DiscordActivity]| {
+            activities
+                .iter()
+                .map(|activity| activity.name.clone())
+                .collect::<Vec<_>>()
+        };
+        tracker.observe(None, presence(DiscordStatus::Online, Some("Hades")));
+
+        // Switching games alone is not emitted.
+        let update = tracker.observe(None, presence(DiscordStatus::Online, Some("Dota 2")));
+        assert!(update.events.is_empty());
+
+        let update = tracker.observe(None, presence(DiscordStatus::Idle, Some("Dota 2")));
+        let event = &update.events[0];
+        assert_eq!(names(&event.activities_started), ["Dota 2"]);
+        assert_eq!(names(&event.activities_ended), ["Hades"]);
+
+        let update = tracker.observe(None, presence(DiscordStatus::Online, Some("Dota 2")));
+        let event = &update.events[0];
+        assert!(event.activities_started.is_empty());
+        assert!(event.activities_ended.is_empty());
+    }
+
+    #[test]
+    fn rate_limited_tracks_are_skipped_in_previous_music() {
+        let clock = ManualClock::new(start());
+        let mut tracker = tracker(
+            target("emit_on_track_change = true\ntrack_change_min_interval_seconds = 60"),
+            &clock,
+        );
+        let playing = |track: &str| {
+            let mut presence = presence(DiscordStatus::Online, Some("Spotify"));
+            presence.activity_fingerprint = format!("Spotify:{track}");
+            presence.music = Some(MusicContext {
+                track: track.to_string(),
+                artist: None,
+                album: None,
+                track_id: None,
+                track_url: None,
+                album_art_url: None,
+                started_at: None,
+                ends_at: None,
+                duration_seconds: None,
+                position_seconds: None,
+                progress: None,
+            });
+            presence
+        };
+        let previous_track = |update: PresenceUpdate| {
+            update
+                .events
+                .first()
+                .and_then(|event| event.previous_music.as_ref())
+                .map(|music| music.track.clone())
+        };
+        tracker.observe(None, playing("One More Time"));
+
+        clock.set(start() + TimeDelta::seconds(100));
+        let update = tracker.observe(None, playing("Aerodynamic"));
+        assert_eq!(previous_track(update).as_deref(), Some("One More Time"));
+
+        clock.set(start() + TimeDelta::seconds(110));
+        assert!(
+            tracker
+                .observe(None, playing("Digital Love"))
+                .events
+                .is_empty()
+        );
+
+        clock.set(start() + TimeDelta::seconds(200));
+        let update = tracker.observe(None, playing("Harder, Better, Faster, Stronger"));
+        assert_eq!(previous_track(update).as_deref(), Some("Aerodynamic"));
+    }
+
+    #[test]
+    fn platforms_after_restart_are_not_a_change() {
+        let clock = ManualClock::new(start());
+        let mut tracker = PresenceTracker::new(
+            target("emit_on_platform_change = true"),
+            reminders(),
+            Some(DiscordStatus::Online),
+            Arc::new(clock.clone()),
+        );
+        let on = |desktop, mobile| {
+            let mut presence = presence(DiscordStatus::Online, None);
+            presence.client_status = Some(DiscordClientStatus {
+                desktop,
+                mobile,
+                web: None,
+            });
+            presence
+        };
+
+        let update = tracker.observe(None, on(Some(DiscordStatus::Online), None));
+        assert!(update.events.is_empty());
+
+        let update = tracker.observe(
+            None,
+            on(Some(DiscordStatus::Online), Some(DiscordStatus::Online)),
+        );
+        assert_eq!(update.events.len(), 1);
+        assert_eq!(update.events[0].kind(), EventKind::PlatformChange);
+    }
+
+    #[test]
+    fn custom_status_after_restart_is_not_a_change() {
+        let clock = ManualClock::new(start());
+        let mut restored = PresenceTracker::new(
+            target("emit_on_custom_status_change = true"),
+            reminders(),
+            Some(DiscordStatus::Online),
+            Arc::new(clock.clone()),
+        );
+        let with_text = |text: &str| {
+            let mut presence = presence(DiscordStatus::Online, None);
+            presence.custom_status = Some(DiscordCustomStatus {
+                emoji: None,
+                text: Some(text.to_string()),
+            });
+            presence
+        };
+
+        assert!(restored.observe(None, with_text("lunch")).events.is_empty());
+        let update = restored.observe(None, with_text("back"));
+        assert_eq!(update.events.len(), 1);
+        assert_eq!(update.events[0].kind(), EventKind::CustomStatusChange);
+
+        let mut rich_presence_only = tracker(
+            target("emit_on_custom_status_change = true\nrich_presence_only = true"),
+            &clock,
+        );
+        rich_presence_only.observe(None, with_text("lunch"));
+        assert!(
+            rich_presence_only
+                .observe(None, with_text("back"))
+                .events
+                .is_empty()
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn changes_mirror_observed_presences(steps in presences()) {
+            let clock = ManualClock::new(start());
+            let mut tracker = tracker(target(""), &clock);
+            let mut now = start();
+            let mut previous: Option<(DiscordStatus, Option<usize>)> = None;
+
+            for (status, game, gap) in steps {
+                now += TimeDelta::seconds(gap);
+                clock.set(now);
+                let status = STATUSES[status];
+                let update = tracker.observe(None, presence(status, game.map(|game| GAMES[game])));
+
+                prop_assert_eq!(update.observed_at, now);
+                prop_assert_eq!(update.status_changed, previous.is_none_or(|(s, _)| s != status));
+                match previous {
+                    Some(previous) if previous != (status, game) => {
+                        prop_assert_eq!(update.events.len(), 1);
+                        let event = &update.events[0];
+                        prop_assert_eq!(event.previous_status, Some(previous.0));
+                        prop_assert_eq!(event.current_status, status);
+                        prop_assert_eq!(
+                            event.activity.as_ref().map(|activity| activity.name.as_str()),
+                            game.map(|game| GAMES[game])
+                        );
+                    }
+                    _ => prop_assert!(update.events.is_empty()),
+                }
+                previous = Some((status, game));
+            }
+        }
+
+        #[test]
+        fn reminders_are_never_early_and_never_repeat(steps in presences()) {
+            let clock = ManualClock::new(start());
+            let mut tracker = tracker(target(""), &clock);
+            let mut now = start();
+            let mut status_since = start();
+            let mut last_sequence = 0;
+
+            for (status, game, gap) in steps {
+                // Check like the reminder loop does, once a minute until the next presence.
+                for _ in 0..gap / 60 {
+                    now += TimeDelta::seconds(60);
+                    clock.set(now);
+                    if let Some(event) = tracker.tick_reminder(None) {
+                        let context = event.reminder.expect("reminder context should be set");
+                        prop_assert!(context.sequence > last_sequence);
+                        prop_assert_eq!(context.elapsed_seconds, (now - status_since).num_seconds() as u64);
+                        prop_assert!(context.elapsed_seconds >= context.sequence * 1_800);
+                        last_sequence = context.sequence;
+                    }
+                }
+                now += TimeDelta::seconds(gap % 60);
+                clock.set(now);
+                if tracker.observe(None, presence(STATUSES[status], game.map(|game| GAMES[game]))).status_changed {
+                    status_since = now;
+                    last_sequence = 0;
+                }
+            }
+        }
+
+        #[test]
+        fn rich_presence_only_needs_an_activity(steps in presences()) {
+            let clock = ManualClock::new(start());
+            let mut tracker = tracker(target("rich_presence_only = true"), &clock);
+            let mut now = start();
+
+            for (status, game, gap) in steps {
+                now += TimeDelta::seconds(gap);
+                clock.set(now);
+                let update = tracker.observe(None, presence(STATUSES[status], game.map(|game| GAMES[game])));
+                for event in update.events {
+                    prop_assert!(event.activity.is_some());
+                }
+                if let Some(event) = tracker.tick_reminder(None) {
+                    prop_assert!(event.activity.is_some());
+                }
+            }
+        }
+
+        #[test]
+        fn debounce_coalesces_flapping_into_the_net_change(
+            // All within one 60s window of the first change.
+            flaps in prop::collection::vec((0..STATUSES.len(), 0..3i64), 1..20),
+        ) {
+            let clock = ManualClock::new(start());
+            let mut tracker = tracker(target("status_debounce_seconds = 60"), &clock);
+            tracker.observe(None, presence(DiscordStatus::Online, None));
+            let mut now = start();
+
+            for (status, gap) in &flaps {
+                now += TimeDelta::seconds(*gap);
+                clock.set(now);
+                prop_assert!(tracker.take_debounced().is_empty());
+                let update = tracker.observe(None, presence(STATUSES[*status], None));
+                prop_assert!(update.events.is_empty());
+            }
+
+            clock.set(now + TimeDelta::seconds(60));
+            let last = STATUSES[flaps[flaps.len() - 1].0];
+            match tracker.take_debounced().pop() {
+                Some(event) => {
+                    prop_assert_eq!(event.previous_status, Some(DiscordStatus::Online));
+                    prop_assert_eq!(event.current_status, last);
+                }
+                None => prop_assert_eq!(last, DiscordStatus::Online),
+            }
+            prop_assert!(tracker.pending_due_at().is_none());
+        }
+    }
+}
@@ -27,22 +27,145 @@ impl Display for DiscordStatus {
     }
 }
 
+/// Coarse classification of an event, used for routing it to webhook sinks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    StatusChange,
+    ActivityChange,
+    PlatformChange,
+    CustomStatusChange,
+    TrackChange,
+    Reminder,
+    ReminderEnded,
+    Digest,
+}
+
+impl EventKind {
+    pub const ALL: [Self; 8] = [
+        Self::StatusChange,
+        Self::ActivityChange,
+        Self::PlatformChange,
+        Self::CustomStatusChange,
+        Self::TrackChange,
+        Self::Reminder,
+        Self::ReminderEnded,
+        Self::Digest,
+    ];
+}
+
+impl Display for EventKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::StatusChange => write!(f, "status_change"),
+            Self::ActivityChange => write!(f, "activity_change"),
+            Self::PlatformChange => write!(f, "platform_change"),
+            Self::CustomStatusChange => write!(f, "custom_status_change"),
+            Self::TrackChange => write!(f, "track_change"),
+            Self::Reminder => write!(f, "reminder"),
+            Self::ReminderEnded => write!(f, "reminder_ended"),
+            Self::Digest => write!(f, "digest"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DiscordStatusChangedEvent {
-    pub source: &'static str,
+    pub source: String,
     pub user_id: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub guild_id: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub previous_status: Option<DiscordStatus>,
     pub current_status: DiscordStatus,
+    /// Status per platform the user is connected from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_status: Option<DiscordClientStatus>,
+    /// Per-platform status before the change; only set on `platform_change` events.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_client_status: Option<DiscordClientStatus>,
+    /// Custom status ("🍜 lunch break"); not part of `activity` or `activities`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_status: Option<DiscordCustomStatus>,
+    /// Custom status before the change; only set on `custom_status_change` events.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_custom_status: Option<DiscordCustomStatus>,
+    /// Primary activity, kept for consumers that only handle one.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub activity: Option<DiscordActivityContext>,
+    /// Every activity currently shown, in the order Discord reports them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub activities: Vec<DiscordActivity>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub activities_started: Vec<DiscordActivity>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub activities_ended: Vec<DiscordActivity>,
+    /// Track currently playing on Spotify.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub music: Option<MusicContext>,
+    /// Track playing before the change; only set on `track_change` events.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_music: Option<MusicContext>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reminder: Option<ReminderContext>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub digest: Option<DigestContext>,
+    /// Set when the event was produced during quiet hours with the
+    /// `next_heartbeat` action; OpenClaw sinks then wake with `next-heartbeat`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub quiet_hours: bool,
     pub observed_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiscordClientStatus {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desktop: Option<DiscordStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mobile: Option<DiscordStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub web: Option<DiscordStatus>,
+}
+
+impl Display for DiscordClientStatus {
+    /// Formats as `desktop=online, mobile=idle`, or `none` without any platform.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let platforms: Vec<String> = [
+            ("desktop", self.desktop),
+            ("mobile", self.mobile),
+            ("web", self.web),
+        ]
+        .into_iter()
+        .filter_map(|(platform, status)| status.map(|status| format!("{platform}={status}")))
+        .collect();
+        if platforms.is_empty() {
+            write!(f, "none")
+        } else {
+            write!(f, "{}", platforms.join(", "))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiscordCustomStatus {
+    /// Unicode emoji, or `:name:` for a custom guild emoji.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emoji: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+impl Display for DiscordCustomStatus {
+    /// Formats as `🍜 lunch break`, or `none` when both parts are empty.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (self.emoji.as_deref(), self.text.as_deref()) {
+            (Some(emoji), Some(text)) => write!(f, "{emoji} {text}"),
+            (Some(value), None) | (None, Some(value)) => write!(f, "{value}"),
+            (None, None) => write!(f, "none"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DiscordActivityContext {
     pub name: String,
@@ -52,13 +175,217 @@ pub struct DiscordActivityContext {
     pub state: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub steam_app_id: Option<u32>,
+    /// When the activity started, as reported by Discord.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<DateTime<Utc>>,
+    /// When the activity ends, as reported by Discord (e.g. a timed match).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+impl DiscordActivityContext {
+    /// How long the activity has been running at `now`, according to Discord.
+    pub fn elapsed_seconds(&self, now: DateTime<Utc>) -> Option<u64> {
+        let started_at = self.started_at?;
+        Some((now - started_at).num_seconds().max(0) as u64)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityKind {
+    Playing,
+    Streaming,
+    Listening,
+    Watching,
+    Custom,
+    Competing,
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiscordActivity {
+    pub kind: ActivityKind,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub application_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub steam_app_id: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamps: Option<ActivityTimestamps>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assets: Option<ActivityAssets>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub buttons: Vec<ActivityButton>,
+}
+
+impl DiscordActivity {
+    /// Whether both describe the same running activity, ignoring details that
+    /// change while it runs (state text, assets, timestamps).
+    pub fn is_same_activity(&self, other: &Self) -> bool {
+        self.kind == other.kind
+            && self.name == other.name
+            && self.application_id == other.application_id
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActivityTimestamps {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActivityAssets {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub large_image: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub large_text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub small_image: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub small_text: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActivityButton {
+    pub label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// Spotify track derived from a `listening` activity.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MusicContext {
+    pub track: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub artist: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub album: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub track_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub track_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub album_art_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ends_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_seconds: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position_seconds: Option<u64>,
+    /// Playback position as a fraction of the track length, from 0.0 to 1.0.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<f64>,
+}
+
+impl MusicContext {
+    /// Whether both describe the same track, ignoring playback position.
+    pub fn is_same_track(&self, other: &Self) -> bool {
+        match (&self.track_id, &other.track_id) {
+            (Some(id), Some(other_id)) => id == other_id,
+            _ => self.track == other.track && self.artist == other.artist,
+        }
+    }
+
+    /// Recomputes the playback position and progress as of `now`.
+    pub fn at(mut self, now: DateTime<Utc>) -> Self {
+        let (Some(started_at), Some(ends_at)) = (self.started_at, self.ends_at) else {
+            return self;
+        };
+        let duration = (ends_at - started_at).num_seconds().max(0) as u64;
+        let position = ((now - started_at).num_seconds().max(0) as u64).min(duration);
+        self.duration_seconds = Some(duration);
+        self.position_seconds = Some(position);
+        self.progress = (duration > 0).then(|| position as f64 / duration as f64);
+        self
+    }
+}
+
+impl Display for MusicContext {
+    /// Formats as `Track — Artist (Album)`, leaving out missing parts.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.track)?;
+        if let Some(artist) = &self.artist {
+            write!(f, " — {artist}")?;
+        }
+        if let Some(album) = &self.album {
+            write!(f, " ({album})")?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReminderContext {
+    /// Time since the reminder anchor started; for Steam-only reminders that is
+    /// the game's start time as reported by Discord, when known.
     pub elapsed_seconds: u64,
     pub interval_seconds: u64,
     pub sequence: u64,
+    /// How long the current activity has been running, according to Discord.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub activity_elapsed_seconds: Option<u64>,
+    /// Name of the `[[reminder.rules]]` entry that anchored the reminder.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+    /// Set on the final event reporting that the reminded state ended; the event
+    /// then carries the ended activity and the status it was in as previous.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub ended: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestPeriod {
+    Daily,
+    Weekly,
+}
+
+impl Display for DigestPeriod {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Daily => write!(f, "daily"),
+            Self::Weekly => write!(f, "weekly"),
+        }
+    }
+}
+
+/// Summary of a target's presence sessions over a digest period.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DigestContext {
+    pub period: DigestPeriod,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    /// Time spent online, idle or dnd.
+    pub online_seconds: u64,
+    pub statuses: Vec<DigestStatusTotal>,
+    pub activities: Vec<DigestActivityTotal>,
+    pub steam_games: Vec<DigestActivityTotal>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DigestStatusTotal {
+    pub status: DiscordStatus,
+    pub seconds: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DigestActivityTotal {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub steam_app_id: Option<u32>,
+    pub seconds: u64,
 }
 
 impl DiscordStatusChangedEvent {
@@ -71,20 +398,151 @@ impl DiscordStatusChangedEvent {
         reminder: Option<ReminderContext>,
     ) -> Self {
         Self {
-            source: "discord.status",
+            source: "discord.status".to_string(),
             user_id,
             guild_id,
             previous_status,
             current_status,
+            client_status: None,
+            previous_client_status: None,
+            custom_status: None,
+            previous_custom_status: None,
             activity,
+            activities: Vec::new(),
+            activities_started: Vec::new(),
+            activities_ended: Vec::new(),
+            music: None,
+            previous_music: None,
             reminder,
+            digest: None,
+            quiet_hours: false,
             observed_at: Utc::now(),
         }
     }
 
+    pub fn new_digest(
+        user_id: u64,
+        guild_id: Option<u64>,
+        current_status: DiscordStatus,
+        digest: DigestContext,
+    ) -> Self {
+        Self {
+            source: "discord.digest".to_string(),
+            digest: Some(digest),
+            ..Self::new(user_id, guild_id, None, current_status, None, None)
+        }
+    }
+
+    /// Attaches the full activity list and the activities that started or ended
+    /// since `previous`.
+    pub fn with_activities(
+        mut self,
+        activities: Vec<DiscordActivity>,
+        previous: &[DiscordActivity],
+    ) -> Self {
+        self.activities_started = activities
+            .iter()
+            .filter(|activity| !previous.iter().any(|p| p.is_same_activity(activity)))
+            .cloned()
+            .collect();
+        self.activities_ended = previous
+            .iter()
+            .filter(|p| {
+                !activities
+                    .iter()
+                    .any(|activity| activity.is_same_activity(p))
+            })
+            .cloned()
+            .collect();
+        self.activities = activities;
+        self
+    }
+
+    /// Attaches the per-platform status. Passing `previous` marks the event as a
+    /// platform change.
+    pub fn with_client_status(
+        mut self,
+        client_status: Option<DiscordClientStatus>,
+        previous: Option<DiscordClientStatus>,
+    ) -> Self {
+        self.client_status = client_status;
+        self.previous_client_status = previous;
+        self
+    }
+
+    /// Attaches the custom status. Passing `previous` marks the event as a custom
+    /// status change.
+    pub fn with_custom_status(
+        mut self,
+        custom_status: Option<DiscordCustomStatus>,
+        previous: Option<DiscordCustomStatus>,
+    ) -> Self {
+        self.custom_status = custom_status;
+        self.previous_custom_status = previous;
+        self
+    }
+
+    /// Attaches the Spotify track. Passing `previous` marks the event as a track
+    /// change.
+    pub fn with_music(
+        mut self,
+        music: Option<MusicContext>,
+        previous: Option<MusicContext>,
+    ) -> Self {
+        self.music = music;
+        self.previous_music = previous;
+        self
+    }
+
+    /// Stamps the event with the time it was observed, which differs from the wall
+    /// clock when recorded presences are replayed.
+    pub fn with_observed_at(mut self, observed_at: DateTime<Utc>) -> Self {
+        self.observed_at = observed_at;
+        self
+    }
+
+    pub fn kind(&self) -> EventKind {
+        if self.digest.is_some() {
+            EventKind::Digest
+        } else if let Some(reminder) = &self.reminder {
+            if reminder.ended {
+                EventKind::ReminderEnded
+            } else {
+                EventKind::Reminder
+            }
+        } else if self.previous_status != Some(self.current_status) {
+            EventKind::StatusChange
+        } else if self.previous_client_status.is_some() {
+            EventKind::PlatformChange
+        } else if self.previous_custom_status.is_some() {
+            EventKind::CustomStatusChange
+        } else if self.previous_music.is_some() {
+            EventKind::TrackChange
+        } else {
+            EventKind::ActivityChange
+        }
+    }
+
     pub fn to_base_text(&self) -> String {
+        if let Some(digest) = &self.digest {
+            return self.digest_text(digest);
+        }
+
+        if let Some(reminder) = self.reminder.as_ref().filter(|reminder| reminder.ended) {
+            return self.reminder_ended_text(reminder);
+        }
+
         if let Some(reminder) = &self.reminder {
-            let elapsed = format_elapsed(reminder.elapsed_seconds);
+            let mut elapsed = format_elapsed(reminder.elapsed_seconds);
+            if let (Some(activity), Some(activity_elapsed)) =
+                (&self.activity, reminder.activity_elapsed_seconds)
+            {
+                elapsed.push_str(&format!(
+                    ", {} running for {}",
+                    activity.name,
+                    format_elapsed(activity_elapsed)
+                ));
+            }
             return match self.guild_id {
                 Some(guild_id) => format!(
                     "Discord status reminder: user {} in guild {} is still {}. Elapsed: {} (reminder #{}) at {}",
@@ -106,6 +564,83 @@ impl DiscordStatusChangedEvent {
             };
         }
 
+        if let Some(previous_client_status) = &self.previous_client_status {
+            let current_client_status = self.client_status.unwrap_or_default();
+            return match self.guild_id {
+                Some(guild_id) => format!(
+                    "Discord platform changed: user {} in guild {} from [{}] to [{}] (status {}) at {}",
+                    self.user_id,
+                    guild_id,
+                    previous_client_status,
+                    current_client_status,
+                    self.current_status,
+                    self.observed_at.to_rfc3339()
+                ),
+                None => format!(
+                    "Discord platform changed: user {} from [{}] to [{}] (status {}) at {}",
+                    self.user_id,
+                    previous_client_status,
+                    current_client_status,
+                    self.current_status,
+                    self.observed_at.to_rfc3339()
+                ),
+            };
+        }
+
+        if let Some(previous_custom_status) = &self.previous_custom_status {
+            let current = self
+                .custom_status
+                .as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_else(|| "none".to_string());
+            return match self.guild_id {
+                Some(guild_id) => format!(
+                    "Discord custom status changed: user {} in guild {} from [{}] to [{}] (status {}) at {}",
+                    self.user_id,
+                    guild_id,
+                    previous_custom_status,
+                    current,
+                    self.current_status,
+                    self.observed_at.to_rfc3339()
+                ),
+                None => format!(
+                    "Discord custom status changed: user {} from [{}] to [{}] (status {}) at {}",
+                    self.user_id,
+                    previous_custom_status,
+                    current,
+                    self.current_status,
+                    self.observed_at.to_rfc3339()
+                ),
+            };
+        }
+
+        if let Some(previous_music) = &self.previous_music {
+            let current = self
+                .music
+                .as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_else(|| "none".to_string());
+            return match self.guild_id {
+                Some(guild_id) => format!(
+                    "Discord track changed: user {} in guild {} from [{}] to [{}] (status {}) at {}",
+                    self.user_id,
+                    guild_id,
+                    previous_music,
+                    current,
+                    self.current_status,
+                    self.observed_at.to_rfc3339()
+                ),
+                None => format!(
+                    "Discord track changed: user {} from [{}] to [{}] (status {}) at {}",
+                    self.user_id,
+                    previous_music,
+                    current,
+                    self.current_status,
+                    self.observed_at.to_rfc3339()
+                ),
+            };
+        }
+
         if self.previous_status == Some(self.current_status) && self.activity.is_some() {
             return match self.guild_id {
                 Some(guild_id) => format!(
@@ -147,9 +682,81 @@ impl DiscordStatusChangedEvent {
             ),
         }
     }
+
+    fn reminder_ended_text(&self, reminder: &ReminderContext) -> String {
+        let target = match self.guild_id {
+            Some(guild_id) => format!("user {} in guild {}", self.user_id, guild_id),
+            None => format!("user {}", self.user_id),
+        };
+        let ended = match (&self.activity, self.previous_status) {
+            (Some(activity), _) => format!("finished {}", activity.name),
+            (None, Some(status)) => format!("is no longer {status}"),
+            (None, None) => "ended".to_string(),
+        };
+        format!(
+            "Discord reminder ended: {} {} after {} ({} reminders), now {} at {}",
+            target,
+            ended,
+            format_elapsed(reminder.elapsed_seconds),
+            reminder.sequence,
+            self.current_status,
+            self.observed_at.to_rfc3339()
+        )
+    }
+
+    fn digest_text(&self, digest: &DigestContext) -> String {
+        let target = match self.guild_id {
+            Some(guild_id) => format!("user {} in guild {}", self.user_id, guild_id),
+            None => format!("user {}", self.user_id),
+        };
+        let mut lines = vec![
+            format!(
+                "Discord {} digest: {} from {} to {}",
+                digest.period,
+                target,
+                digest.period_start.to_rfc3339(),
+                digest.period_end.to_rfc3339()
+            ),
+            format!("Online time: {}", format_elapsed(digest.online_seconds)),
+        ];
+
+        if !digest.statuses.is_empty() {
+            let statuses = digest
+                .statuses
+                .iter()
+                .map(|total| format!("{} {}", total.status, format_elapsed(total.seconds)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            lines.push(format!("Statuses: {statuses}"));
+        }
+        if !digest.activities.is_empty() {
+            lines.push("Top activities:".to_string());
+            for total in &digest.activities {
+                lines.push(format!(
+                    "- {}: {}",
+                    total.name,
+                    format_elapsed(total.seconds)
+                ));
+            }
+        }
+        if !digest.steam_games.is_empty() {
+            lines.push("Steam games:".to_string());
+            for total in &digest.steam_games {
+                let app_id = total.steam_app_id.unwrap_or_default();
+                lines.push(format!(
+                    "- {} (app_id={}): {}",
+                    total.name,
+                    app_id,
+                    format_elapsed(total.seconds)
+                ));
+            }
+        }
+
+        lines.join("\n")
+    }
 }
 
-fn format_elapsed(seconds: u64) -> String {
+pub fn format_elapsed(seconds: u64) -> String {
     let hours = seconds / 3600;
     let minutes = (seconds % 3600) / 60;
     let secs = seconds % 60;
@@ -180,6 +787,7 @@ mod tests {
         let text = event.to_base_text();
         assert!(text.contains("from offline to online"));
         assert!(text.contains("guild 99"));
+        assert_eq!(event.kind(), EventKind::StatusChange);
     }
 
     #[test]
@@ -194,11 +802,74 @@ mod tests {
                 elapsed_seconds: 1800,
                 interval_seconds: 1800,
                 sequence: 1,
+                activity_elapsed_seconds: None,
+                rule: None,
+                ended: false,
             }),
         );
         let text = event.to_base_text();
         assert!(text.contains("status reminder"));
         assert!(text.contains("30m"));
+        assert_eq!(event.kind(), EventKind::Reminder);
+    }
+
+    #[test]
+    fn reminder_text_reports_activity_runtime() {
+        let now = Utc::now();
+        let activity = DiscordActivityContext {
+            name: "Dota 2".to_string(),
+            details: None,
+            state: None,
+            steam_app_id: Some(570),
+            started_at: Some(now - chrono::TimeDelta::minutes(95)),
+            ends_at: None,
+        };
+        let activity_elapsed_seconds = activity.elapsed_seconds(now);
+        assert_eq!(activity_elapsed_seconds, Some(5700));
+
+        let event = DiscordStatusChangedEvent::new(
+            42,
+            None,
+            None,
+            DiscordStatus::Online,
+            Some(activity),
+            Some(ReminderContext {
+                elapsed_seconds: 1800,
+                interval_seconds: 1800,
+                sequence: 1,
+                activity_elapsed_seconds,
+                rule: None,
+                ended: false,
+            }),
+        );
+        assert!(
+            event
+                .to_base_text()
+                .contains("Elapsed: 30m 0s, Dota 2 running for 1h 35m 0s (reminder #1)")
+        );
+    }
+
+    #[test]
+    fn reminder_ended_text_reports_duration() {
+        let event = DiscordStatusChangedEvent::new(
+            42,
+            None,
+            Some(DiscordStatus::Dnd),
+            DiscordStatus::Online,
+            None,
+            Some(ReminderContext {
+                elapsed_seconds: 7800,
+                interval_seconds: 3600,
+                sequence: 2,
+                activity_elapsed_seconds: None,
+                rule: Some("focus".to_string()),
+                ended: true,
+            }),
+        );
+        assert_eq!(event.kind(), EventKind::ReminderEnded);
+        assert!(event.to_base_text().starts_with(
+            "Discord reminder ended: user 42 is no longer dnd after 2h 10m 0s (2 reminders), now online"
+        ));
     }
 
     #[test]
@@ -213,10 +884,191 @@ mod tests {
                 details: None,
                 state: None,
                 steam_app_id: Some(1091500),
+                started_at: None,
+                ends_at: None,
             }),
             None,
         );
         let text = event.to_base_text();
         assert!(text.contains("activity changed"));
+        assert_eq!(event.kind(), EventKind::ActivityChange);
+    }
+
+    #[test]
+    fn digest_text_lists_totals() {
+        let start = DateTime::from_timestamp(1_770_000_000, 0).expect("valid timestamp");
+        let event = DiscordStatusChangedEvent::new_digest(
+            42,
+            None,
+            DiscordStatus::Online,
+            DigestContext {
+                period: DigestPeriod::Daily,
+                period_start: start,
+                period_end: start + chrono::TimeDelta::days(1),
+                online_seconds: 5400,
+                statuses: vec![DigestStatusTotal {
+                    status: DiscordStatus::Online,
+                    seconds: 5400,
+                }],
+                activities: vec![DigestActivityTotal {
+                    name: "Dota 2".to_string(),
+                    steam_app_id: Some(570),
+                    seconds: 3600,
+                }],
+                steam_games: vec![DigestActivityTotal {
+                    name: "Dota 2".to_string(),
+                    steam_app_id: Some(570),
+                    seconds: 3600,
+                }],
+            },
+        );
+        let text = event.to_base_text();
+        assert!(text.starts_with("Discord daily digest: user 42"));
+        assert!(text.contains("Online time: 1h 30m 0s"));
+        assert!(text.contains("- Dota 2: 1h 0m 0s"));
+        assert!(text.contains("- Dota 2 (app_id=570): 1h 0m 0s"));
+        assert_eq!(event.kind(), EventKind::Digest);
+    }
+
+    #[test]
+    fn with_activities_diffs_started_and_ended() {
+        let activity = |kind, name: &str| DiscordActivity {
+            kind,
+            name: name.to_string(),
+            details: None,
+            state: None,
+            application_id: None,
+            steam_app_id: None,
+            url: None,
+            timestamps: None,
+            assets: None,
+            buttons: Vec::new(),
+        };
+        let previous = vec![
+            activity(ActivityKind::Playing, "Dota 2"),
+            activity(ActivityKind::Listening, "Spotify"),
+        ];
+        let mut still_playing = activity(ActivityKind::Playing, "Dota 2");
+        still_playing.state = Some("In Match".to_string());
+        let current = vec![
+            still_playing,
+            activity(ActivityKind::Playing, "Visual Studio Code"),
+        ];
+
+        let event = DiscordStatusChangedEvent::new(
+            42,
+            None,
+            Some(DiscordStatus::Online),
+            DiscordStatus::Online,
+            None,
+            None,
+        )
+        .with_activities(current, &previous);
+
+        assert_eq!(event.activities.len(), 2);
+        let started: Vec<_> = event
+            .activities_started
+            .iter()
+            .map(|a| a.name.as_str())
+            .collect();
+        let ended: Vec<_> = event
+            .activities_ended
+            .iter()
+            .map(|a| a.name.as_str())
+            .collect();
+        assert_eq!(started, vec!["Visual Studio Code"]);
+        assert_eq!(ended, vec!["Spotify"]);
+    }
+
+    #[test]
+    fn platform_change_text_lists_platforms() {
+        let event = DiscordStatusChangedEvent::new(
+            42,
+            None,
+            Some(DiscordStatus::Online),
+            DiscordStatus::Online,
+            None,
+            None,
+        )
+        .with_client_status(
+            Some(DiscordClientStatus {
+                mobile: Some(DiscordStatus::Online),
+                ..DiscordClientStatus::default()
+            }),
+            Some(DiscordClientStatus {
+                desktop: Some(DiscordStatus::Online),
+                ..DiscordClientStatus::default()
+            }),
+        );
+        assert_eq!(event.kind(), EventKind::PlatformChange);
+        assert!(
+            event
+                .to_base_text()
+                .contains("from [desktop=online] to [mobile=online]")
+        );
+    }
+
+    #[test]
+    fn custom_status_change_text() {
+        let event = DiscordStatusChangedEvent::new(
+            42,
+            None,
+            Some(DiscordStatus::Online),
+            DiscordStatus::Online,
+            None,
+            None,
+        )
+        .with_custom_status(
+            Some(DiscordCustomStatus {
+                emoji: Some("🍜".to_string()),
+                text: Some("lunch break".to_string()),
+            }),
+            Some(DiscordCustomStatus::default()),
+        );
+        assert_eq!(event.kind(), EventKind::CustomStatusChange);
+        assert!(
+            event
+                .to_base_text()
+                .contains("from [none] to [🍜 lunch break]")
+        );
+    }
+
+    #[test]
+    fn track_change_text_and_progress() {
+        let start = DateTime::from_timestamp(1_770_000_000, 0).expect("valid timestamp");
+        let track = |track: &str, id: &str| MusicContext {
+            track: track.to_string(),
+            artist: Some("Daft Punk".to_string()),
+            album: Some("Discovery".to_string()),
+            track_id: Some(id.to_string()),
+            track_url: Some(format!("https://open.spotify.com/track/{id}")),
+            album_art_url: None,
+            started_at: Some(start),
+            ends_at: Some(start + chrono::TimeDelta::seconds(200)),
+            duration_seconds: None,
+            position_seconds: None,
+            progress: None,
+        };
+        let current = track("Digital Love", "2VEZx7NWsZ1D0eJ4uv5Fym")
+            .at(start + chrono::TimeDelta::seconds(50));
+        assert_eq!(current.duration_seconds, Some(200));
+        assert_eq!(current.position_seconds, Some(50));
+        assert_eq!(current.progress, Some(0.25));
+
+        let previous = track("One More Time", "0DiWol3AO6WpXZgp0goxAV");
+        assert!(!current.is_same_track(&previous));
+        let event = DiscordStatusChangedEvent::new(
+            42,
+            None,
+            Some(DiscordStatus::Online),
+            DiscordStatus::Online,
+            None,
+            None,
+        )
+        .with_music(Some(current), Some(previous));
+        assert_eq!(event.kind(), EventKind::TrackChange);
+        assert!(event.to_base_text().contains(
+            "from [One More Time — Daft Punk (Discovery)] to [Digital Love — Daft Punk (Discovery)]"
+        ));
     }
 }
@@ -0,0 +1,516 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OptionalExtension, params};
+use tokio::task;
+use tracing::{info, warn};
+
+use crate::config::HistorySettings;
+use crate::event::{DiscordActivityContext, DiscordStatus};
+
+const HISTORY_TABLE_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS presence_sessions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    guild_id INTEGER,
+    status TEXT NOT NULL,
+    activity_name TEXT,
+    activity_details TEXT,
+    steam_app_id INTEGER,
+    started_at INTEGER NOT NULL,
+    ended_at INTEGER,
+    duration_seconds INTEGER,
+    last_seen_at INTEGER
+);
+CREATE INDEX IF NOT EXISTS idx_presence_sessions_user_started
+    ON presence_sessions(user_id, started_at);
+CREATE INDEX IF NOT EXISTS idx_presence_sessions_open
+    ON presence_sessions(user_id, guild_id) WHERE ended_at IS NULL;
+"#;
+
+/// Adds `last_seen_at` to databases created before it existed.
+const HISTORY_LAST_SEEN_MIGRATION: &str =
+    "ALTER TABLE presence_sessions ADD COLUMN last_seen_at INTEGER";
+
+/// Sessions left open when the process stopped end at their `last_seen_at`.
+const CLOSE_STALE_SESSIONS_SQL: &str = r#"
+UPDATE presence_sessions
+SET ended_at = MAX(COALESCE(last_seen_at, started_at), started_at),
+    duration_seconds = MAX(COALESCE(last_seen_at, started_at) - started_at, 0)
+WHERE ended_at IS NULL
+"#;
+
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Log of presence sessions: one row per continuous stretch of the same status
+/// and activity for a monitored target.
+///
+/// A session stays open (`ended_at IS NULL`) until a presence update with a
+/// different status or activity arrives, which closes it and opens the next one.
+/// Open sessions are known to last until `last_seen_at`, which presence updates
+/// and a heartbeat keep current; sessions still open at startup end there, so
+/// downtime is not counted. Times are unix seconds, so playtime can be summed
+/// directly in SQL.
+#[derive(Debug)]
+pub struct SessionHistory {
+    path: PathBuf,
+}
+
+/// Part of a session that falls inside a queried time range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionSpan {
+    pub status: DiscordStatus,
+    pub activity_name: Option<String>,
+    pub steam_app_id: Option<u32>,
+    pub seconds: u64,
+}
+
+/// Identity of a session; a change in any of these fields starts a new session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SessionKey {
+    status: String,
+    activity_name: Option<String>,
+    activity_details: Option<String>,
+    steam_app_id: Option<i64>,
+}
+
+impl SessionKey {
+    fn new(status: DiscordStatus, activity: Option<&DiscordActivityContext>) -> Self {
+        Self {
+            status: status.to_string(),
+            activity_name: activity.map(|a| a.name.clone()),
+            activity_details: activity.and_then(|a| a.details.clone()),
+            steam_app_id: activity.and_then(|a| a.steam_app_id).map(i64::from),
+        }
+    }
+}
+
+impl SessionHistory {
+    pub async fn open(settings: &HistorySettings) -> Result<Self> {
+        let history = Self {
+            path: settings.sqlite_path.clone(),
+        };
+
+        let path = history.path.clone();
+        task::spawn_blocking(move || -> Result<()> {
+            if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                fs::create_dir_all(parent).with_context(|| {
+                    format!(
+                        "failed to create history directory for sqlite database: {}",
+                        parent.display()
+                    )
+                })?;
+            }
+
+            let conn = open_connection(&path)?;
+            conn.execute_batch(HISTORY_TABLE_SQL)
+                .context("failed to initialize sqlite history schema")?;
+            if !has_column(&conn, "presence_sessions", "last_seen_at")? {
+                conn.execute_batch(HISTORY_LAST_SEEN_MIGRATION)
+                    .context("failed to migrate presence_sessions table")?;
+            }
+            let closed = conn
+                .execute(CLOSE_STALE_SESSIONS_SQL, [])
+                .context("failed to close presence sessions left open")?;
+            if closed > 0 {
+                info!(
+                    closed,
+                    "closed presence sessions left open at their last sighting"
+                );
+            }
+            Ok(())
+        })
+        .await
+        .context("sqlite history init task join failed")??;
+
+        Ok(history)
+    }
+
+    /// Records the presence observed at `at`. Closes the open session of the target
+    /// and opens a new one when status or activity differ; otherwise does nothing.
+    pub async fn record(
+        &self,
+        user_id: u64,
+        guild_id: Option<u64>,
+        status: DiscordStatus,
+        activity: Option<&DiscordActivityContext>,
+        at: DateTime<Utc>,
+    ) -> Result<()> {
+        let key = SessionKey::new(status, activity);
+        let user_id = user_id as i64;
+        let guild_id = guild_id.map(|id| id as i64);
+        let at = at.timestamp();
+
+        self.with_connection(move |conn| {
+            let tx = conn
+                .transaction()
+                .context("failed to begin history transaction")?;
+            let open: Option<(i64, SessionKey, i64)> = tx
+                .query_row(
+                    r#"
+                    SELECT id, status, activity_name, activity_details, steam_app_id, started_at
+                    FROM presence_sessions
+                    WHERE user_id = ?1 AND guild_id IS ?2 AND ended_at IS NULL
+                    ORDER BY id DESC
+                    LIMIT 1
+                    "#,
+                    params![user_id, guild_id],
+                    |row| {
+                        Ok((
+                            row.get(0)?,
+                            SessionKey {
+                                status: row.get(1)?,
+                                activity_name: row.get(2)?,
+                                activity_details: row.get(3)?,
+                                steam_app_id: row.get(4)?,
+                            },
+                            row.get(5)?,
+                        ))
+                    },
+                )
+                .optional()
+                .context("failed to query open presence session")?;
+
+            if let Some((id, open_key, started_at)) = open {
+                if open_key == key {
+                    tx.execute(
+                        "UPDATE presence_sessions SET last_seen_at = ?2 WHERE id = ?1",
+                        params![id, at],
+                    )
+                    .context("failed to update presence session")?;
+                    tx.commit().context("failed to commit history transaction")?;
+                    return Ok(());
+                }
+                tx.execute(
+                    "UPDATE presence_sessions SET ended_at = ?2, duration_seconds = ?3 WHERE id = ?1",
+                    params![id, at, at.saturating_sub(started_at).max(0)],
+                )
+                .context("failed to close presence session")?;
+            }
+
+            tx.execute(
+                r#"
+                INSERT INTO presence_sessions
+                    (user_id, guild_id, status, activity_name, activity_details, steam_app_id, started_at, last_seen_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?7)
+                "#,
+                params![
+                    user_id,
+                    guild_id,
+                    key.status,
+                    key.activity_name,
+                    key.activity_details,
+                    key.steam_app_id,
+                    at
+                ],
+            )
+            .context("failed to open presence session")?;
+            tx.commit().context("failed to commit history transaction")?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Marks every open session as still going at `at`.
+    pub async fn touch(&self, at: DateTime<Utc>) -> Result<()> {
+        let at = at.timestamp();
+        self.with_connection(move |conn| {
+            conn.execute(
+                "UPDATE presence_sessions SET last_seen_at = ?1 WHERE ended_at IS NULL",
+                params![at],
+            )
+            .context("failed to update open presence sessions")?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Returns every session of the target overlapping `[from, to)`, clipped to the
+    /// range. Sessions that are still open are counted up to `to`.
+    pub async fn spans(
+        &self,
+        user_id: u64,
+        guild_id: Option<u64>,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<SessionSpan>> {
+        let user_id = user_id as i64;
+        let guild_id = guild_id.map(|id| id as i64);
+        let from = from.timestamp();
+        let to = to.timestamp();
+
+        self.with_connection(move |conn| {
+            let mut stmt = conn
+                .prepare(
+                    r#"
+                    SELECT status, activity_name, steam_app_id,
+                        MIN(COALESCE(ended_at, ?4), ?4) - MAX(started_at, ?3)
+                    FROM presence_sessions
+                    WHERE user_id = ?1 AND guild_id IS ?2
+                        AND started_at < ?4 AND (ended_at IS NULL OR ended_at > ?3)
+                    ORDER BY id
+                    "#,
+                )
+                .context("failed to prepare presence session query")?;
+            let spans = stmt
+                .query_map(params![user_id, guild_id, from, to], |row| {
+                    let status: String = row.get(0)?;
+                    let steam_app_id: Option<i64> = row.get(2)?;
+                    let seconds: i64 = row.get(3)?;
+                    Ok(SessionSpan {
+                        status: parse_status(&status),
+                        activity_name: row.get(1)?,
+                        steam_app_id: steam_app_id.and_then(|id| u32::try_from(id).ok()),
+                        seconds: seconds.max(0) as u64,
+                    })
+                })
+                .context("failed to query presence sessions")?
+                .collect::<rusqlite::Result<Vec<_>>>()
+                .context("failed to read presence sessions")?;
+            Ok(spans)
+        })
+        .await
+    }
+
+    async fn with_connection<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> Result<T> + Send + 'static,
+    {
+        let path = self.path.clone();
+        task::spawn_blocking(move || {
+            let mut conn = open_connection(&path)?;
+            f(&mut conn)
+        })
+        .await
+        .context("sqlite history task join failed")?
+    }
+}
+
+/// Keeps `last_seen_at` of open sessions current while the process runs.
+pub async fn run_heartbeat(history: Arc<SessionHistory>) {
+    let mut ticker = tokio::time::interval(HEARTBEAT_INTERVAL);
+    loop {
+        ticker.tick().await;
+        if let Err(err) = history.touch(Utc::now()).await {
+            warn!(error = ?err, "failed to update open presence sessions");
+        }
+    }
+}
+
+fn parse_status(raw: &str) -> DiscordStatus {
+    match raw {
+        "online" => DiscordStatus::Online,
+        "idle" => DiscordStatus::Idle,
+        "dnd" => DiscordStatus::Dnd,
+        "offline" => DiscordStatus::Offline,
+        "invisible" => DiscordStatus::Invisible,
+        _ => DiscordStatus::Unknown,
+    }
+}
+
+fn has_column(conn: &Connection, table: &str, column: &str) -> Result<bool> {
+    let mut stmt = conn
+        .prepare(&format!("PRAGMA table_info({table})"))
+        .with_context(|| format!("failed to inspect history table {table}"))?;
+    let names = stmt
+        .query_map([], |r| r.get::<_, String>(1))
+        .with_context(|| format!("failed to read columns of history table {table}"))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(names.iter().any(|name| name == column))
+}
+
+fn open_connection(path: &Path) -> Result<Connection> {
+    Connection::open(path)
+        .with_context(|| format!("failed to open sqlite history DB at {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    type SessionRow = (String, Option<String>, i64, Option<i64>, Option<i64>);
+
+    fn make_test_settings(file: &str) -> HistorySettings {
+        let unique = format!(
+            "statushub_test_{}_{}",
+            file,
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        );
+        HistorySettings {
+            enabled: true,
+            sqlite_path: std::env::temp_dir().join(unique),
+        }
+    }
+
+    fn dota() -> DiscordActivityContext {
+        DiscordActivityContext {
+            name: "Dota 2".to_string(),
+            details: None,
+            state: None,
+            steam_app_id: Some(570),
+            started_at: None,
+            ends_at: None,
+        }
+    }
+
+    async fn load_sessions(history: &SessionHistory) -> Vec<SessionRow> {
+        history
+            .with_connection(|conn| {
+                let mut stmt = conn.prepare(
+                    r#"
+                    SELECT status, activity_name, started_at, ended_at, duration_seconds
+                    FROM presence_sessions ORDER BY id
+                    "#,
+                )?;
+                let rows = stmt
+                    .query_map([], |row| {
+                        Ok((
+                            row.get(0)?,
+                            row.get(1)?,
+                            row.get(2)?,
+                            row.get(3)?,
+                            row.get(4)?,
+                        ))
+                    })?
+                    .collect::<rusqlite::Result<Vec<_>>>()?;
+                Ok(rows)
+            })
+            .await
+            .expect("sessions should load")
+    }
+
+    #[tokio::test]
+    async fn sessions_close_on_next_change() {
+        let settings = make_test_settings("history.sqlite3");
+        let history = SessionHistory::open(&settings)
+            .await
+            .expect("history should open");
+        let at = |seconds: i64| {
+            Utc.timestamp_opt(1_700_000_000 + seconds, 0)
+                .single()
+                .expect("valid timestamp")
+        };
+
+        history
+            .record(1, None, DiscordStatus::Online, None, at(0))
+            .await
+            .expect("record should succeed");
+        history
+            .record(1, None, DiscordStatus::Online, Some(&dota()), at(60))
+            .await
+            .expect("record should succeed");
+        // Same status and activity: the open session continues.
+        history
+            .record(1, None, DiscordStatus::Online, Some(&dota()), at(120))
+            .await
+            .expect("record should succeed");
+        history
+            .record(1, None, DiscordStatus::Idle, None, at(3660))
+            .await
+            .expect("record should succeed");
+
+        let sessions = load_sessions(&history).await;
+        assert_eq!(
+            sessions,
+            vec![
+                (
+                    "online".to_string(),
+                    None,
+                    1_700_000_000,
+                    Some(1_700_000_060),
+                    Some(60)
+                ),
+                (
+                    "online".to_string(),
+                    Some("Dota 2".to_string()),
+                    1_700_000_060,
+                    Some(1_700_003_660),
+                    Some(3600)
+                ),
+                ("idle".to_string(), None, 1_700_003_660, None, None),
+            ]
+        );
+
+        let spans = history
+            .spans(1, None, at(30), at(3700))
+            .await
+            .expect("spans should load");
+        let seconds: Vec<_> = spans
+            .iter()
+            .map(|span| (span.status, span.steam_app_id, span.seconds))
+            .collect();
+        assert_eq!(
+            seconds,
+            vec![
+                (DiscordStatus::Online, None, 30),
+                (DiscordStatus::Online, Some(570), 3600),
+                (DiscordStatus::Idle, None, 40),
+            ]
+        );
+
+        let _ = fs::remove_file(settings.sqlite_path);
+    }
+
+    #[tokio::test]
+    async fn sessions_left_open_end_at_last_sighting_on_restart() {
+        let settings = make_test_settings("history_restart.sqlite3");
+        let history = SessionHistory::open(&settings)
+            .await
+            .expect("history should open");
+        let at = |seconds: i64| {
+            Utc.timestamp_opt(1_700_000_000 + seconds, 0)
+                .single()
+                .expect("valid timestamp")
+        };
+
+        history
+            .record(1, None, DiscordStatus::Online, Some(&dota()), at(0))
+            .await
+            .expect("record should succeed");
+        history
+            .record(1, None, DiscordStatus::Online, Some(&dota()), at(60))
+            .await
+            .expect("record should succeed");
+        history.touch(at(120)).await.expect("touch should succeed");
+        drop(history);
+
+        // Restarted long after: the downtime is not part of the session.
+        let history = SessionHistory::open(&settings)
+            .await
+            .expect("history should open");
+        history
+            .record(1, None, DiscordStatus::Online, Some(&dota()), at(7200))
+            .await
+            .expect("record should succeed");
+
+        let sessions = load_sessions(&history).await;
+        assert_eq!(
+            sessions,
+            vec![
+                (
+                    "online".to_string(),
+                    Some("Dota 2".to_string()),
+                    1_700_000_000,
+                    Some(1_700_000_120),
+                    Some(120)
+                ),
+                (
+                    "online".to_string(),
+                    Some("Dota 2".to_string()),
+                    1_700_007_200,
+                    None,
+                    None
+                ),
+            ]
+        );
+
+        let _ = fs::remove_file(settings.sqlite_path);
+    }
+}
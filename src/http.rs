@@ -0,0 +1,181 @@
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use axum::extract::State;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::http::header;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use futures_util::stream;
+use futures_util::{Stream, StreamExt};
+use serde::Serialize;
+use tokio::net::TcpListener;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, info, warn};
+
+use crate::config::HttpSettings;
+use crate::discord::{PresenceBoard, TargetStatusSnapshot};
+use crate::event::DiscordStatusChangedEvent;
+use crate::hub::{EventHub, Subscription};
+use crate::metrics::metrics;
+
+#[derive(Clone)]
+struct AppState {
+    board: PresenceBoard,
+    hub: EventHub,
+}
+
+#[derive(Debug, Serialize)]
+struct StatusResponse {
+    targets: Vec<TargetStatusSnapshot>,
+}
+
+pub async fn serve(settings: HttpSettings, board: PresenceBoard, hub: EventHub) -> Result<()> {
+    let listener = TcpListener::bind(settings.bind)
+        .await
+        .with_context(|| format!("failed to bind HTTP server to {}", settings.bind))?;
+    info!(bind = %settings.bind, "HTTP API listening");
+
+    axum::serve(listener, router(AppState { board, hub }))
+        .await
+        .context("HTTP server exited unexpectedly")
+}
+
+fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/status", get(status))
+        .route("/events", get(events_sse))
+        .route("/events/ws", get(events_ws))
+        .route("/metrics", get(prometheus_metrics))
+        .with_state(state)
+}
+
+async fn status(State(state): State<AppState>) -> Json<StatusResponse> {
+    Json(StatusResponse {
+        targets: state.board.snapshot().await,
+    })
+}
+
+async fn prometheus_metrics() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics().render(),
+    )
+}
+
+/// Streams events as Server-Sent Events. Each message carries the event kind as
+/// the SSE event name and the same JSON body the generic webhook posts.
+async fn events_sse(
+    State(state): State<AppState>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let stream = subscription_stream(state.hub.subscribe()).map(|event| {
+        let data = serde_json::to_string(event.as_ref()).unwrap_or_default();
+        Ok(Event::default().event(event.kind().to_string()).data(data))
+    });
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+async fn events_ws(State(state): State<AppState>, upgrade: WebSocketUpgrade) -> Response {
+    upgrade.on_upgrade(move |socket| forward_to_websocket(socket, state.hub.subscribe()))
+}
+
+async fn forward_to_websocket(mut socket: WebSocket, subscription: Subscription) {
+    let mut events = Box::pin(subscription_stream(subscription));
+    while let Some(event) = events.next().await {
+        let Ok(data) = serde_json::to_string(event.as_ref()) else {
+            continue;
+        };
+        if socket.send(Message::Text(data.into())).await.is_err() {
+            debug!("WebSocket subscriber disconnected");
+            break;
+        }
+    }
+}
+
+/// Replays the latest events, then yields live events until the hub closes.
+/// Subscribers that fall too far behind skip the missed events.
+fn subscription_stream(
+    subscription: Subscription,
+) -> impl Stream<Item = Arc<DiscordStatusChangedEvent>> {
+    let Subscription { replay, rx } = subscription;
+    let live = stream::unfold(rx, |mut rx| async move {
+        loop {
+            match rx.recv().await {
+                Ok(event) => return Some((event, rx)),
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!(skipped, "event stream subscriber lagged, skipping events");
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+    stream::iter(replay).chain(live)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{DiscordSettings, ReminderSettings};
+    use crate::event::DiscordStatus;
+
+    #[tokio::test]
+    async fn status_lists_every_target() {
+        let settings: DiscordSettings = toml::from_str(
+            r#"
+                bot_token = "token"
+
+                [[targets]]
+                user_id = 1
+
+                [[targets]]
+                user_id = 2
+                guild_id = 20
+            "#,
+        )
+        .expect("settings should parse");
+        let board = PresenceBoard::restore(&settings, &ReminderSettings::default(), None)
+            .await
+            .expect("board should restore");
+        let hub = EventHub::new();
+
+        let Json(response) = status(State(AppState { board, hub })).await;
+        let value = serde_json::to_value(&response).expect("response should serialize");
+
+        let targets = value["targets"].as_array().expect("targets array");
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[0]["user_id"], 1);
+        assert!(targets[0]["status"].is_null());
+        assert_eq!(targets[1]["guild_id"], 20);
+    }
+
+    #[tokio::test]
+    async fn subscription_stream_replays_then_follows() {
+        let hub = EventHub::new();
+        hub.publish(&DiscordStatusChangedEvent::new(
+            1,
+            None,
+            None,
+            DiscordStatus::Online,
+            None,
+            None,
+        ));
+
+        let mut events = Box::pin(subscription_stream(hub.subscribe()));
+        hub.publish(&DiscordStatusChangedEvent::new(
+            1,
+            None,
+            Some(DiscordStatus::Online),
+            DiscordStatus::Idle,
+            None,
+            None,
+        ));
+
+        let first = events.next().await.expect("replayed event");
+        assert_eq!(first.current_status, DiscordStatus::Online);
+        let second = events.next().await.expect("live event");
+        assert_eq!(second.current_status, DiscordStatus::Idle);
+    }
+}
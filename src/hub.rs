@@ -0,0 +1,100 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::broadcast;
+
+use crate::event::{DiscordStatusChangedEvent, EventKind};
+
+const EVENT_HUB_CAPACITY: usize = 256;
+
+/// Broadcasts every produced event to live subscribers (SSE / WebSocket) and
+/// remembers the latest event of each user so new subscribers can be brought up
+/// to date immediately.
+#[derive(Clone)]
+pub struct EventHub {
+    inner: Arc<Mutex<HubState>>,
+}
+
+struct HubState {
+    tx: broadcast::Sender<Arc<DiscordStatusChangedEvent>>,
+    latest: BTreeMap<(u64, Option<u64>), Arc<DiscordStatusChangedEvent>>,
+}
+
+pub struct Subscription {
+    pub replay: Vec<Arc<DiscordStatusChangedEvent>>,
+    pub rx: broadcast::Receiver<Arc<DiscordStatusChangedEvent>>,
+}
+
+impl Default for EventHub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventHub {
+    pub fn new() -> Self {
+        let (tx, _) = broadcast::channel(EVENT_HUB_CAPACITY);
+        Self {
+            inner: Arc::new(Mutex::new(HubState {
+                tx,
+                latest: BTreeMap::new(),
+            })),
+        }
+    }
+
+    pub fn publish(&self, event: &DiscordStatusChangedEvent) {
+        let event = Arc::new(event.clone());
+        let mut state = self.inner.lock().expect("event hub lock poisoned");
+        // Digests summarize the past and would hide the current state on replay.
+        if event.kind() != EventKind::Digest {
+            state
+                .latest
+                .insert((event.user_id, event.guild_id), event.clone());
+        }
+        // Sending only fails when nobody is subscribed, which is fine.
+        let _ = state.tx.send(event);
+    }
+
+    /// Returns the latest event per user together with a receiver for every
+    /// event published afterwards, without gaps or duplicates between the two.
+    pub fn subscribe(&self) -> Subscription {
+        let state = self.inner.lock().expect("event hub lock poisoned");
+        Subscription {
+            replay: state.latest.values().cloned().collect(),
+            rx: state.tx.subscribe(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::DiscordStatus;
+
+    fn make_event(user_id: u64, status: DiscordStatus) -> DiscordStatusChangedEvent {
+        DiscordStatusChangedEvent::new(user_id, None, None, status, None, None)
+    }
+
+    #[tokio::test]
+    async fn subscribe_replays_latest_then_streams() {
+        let hub = EventHub::new();
+        hub.publish(&make_event(1, DiscordStatus::Idle));
+        hub.publish(&make_event(1, DiscordStatus::Online));
+        hub.publish(&make_event(2, DiscordStatus::Dnd));
+
+        let mut subscription = hub.subscribe();
+        let replayed: Vec<_> = subscription
+            .replay
+            .iter()
+            .map(|event| (event.user_id, event.current_status))
+            .collect();
+        assert_eq!(
+            replayed,
+            vec![(1, DiscordStatus::Online), (2, DiscordStatus::Dnd)]
+        );
+
+        hub.publish(&make_event(2, DiscordStatus::Offline));
+        let next = subscription.rx.recv().await.expect("event should arrive");
+        assert_eq!(next.current_status, DiscordStatus::Offline);
+    }
+}
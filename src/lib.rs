@@ -0,0 +1,7 @@
+//! Reusable pieces of StatusHub for webhook receivers.
+//!
+//! The binary is the main product; this library only exposes helpers that code on
+//! the receiving end of StatusHub webhooks may want to share, such as signature
+//! verification.
+
+pub mod signing;
@@ -1,23 +1,42 @@
 mod cache;
 mod config;
+mod delivery;
+mod digest;
 mod discord;
 mod event;
+mod history;
+mod http;
+mod hub;
+mod metrics;
+mod outbox;
+mod quiet_hours;
+mod reload;
+mod rules;
 mod state_cache;
 mod steam;
 mod webhook;
 
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Instant;
 
-use anyhow::Context;
-use clap::Parser;
+use anyhow::{Context, bail};
+use chrono::{DateTime, Utc};
+use clap::{Parser, Subcommand};
+use tokio::sync::{mpsc, watch};
 use tracing::info;
 use tracing_subscriber::EnvFilter;
 
 use crate::cache::CacheService;
 use crate::config::Settings;
+use crate::delivery::Pipeline;
+use crate::discord::{PresenceBoard, PresenceRecorder};
+use crate::event::{DiscordActivityContext, DiscordStatus, DiscordStatusChangedEvent};
+use crate::history::SessionHistory;
+use crate::hub::EventHub;
+use crate::outbox::Outbox;
+use crate::reload::ConfigReloader;
 use crate::state_cache::PersistentStatusCache;
-use crate::webhook::WebhookSender;
 
 #[derive(Debug, Parser)]
 #[command(name = "statushub", about = "Discord status to webhook bridge")]
@@ -25,10 +44,45 @@ struct Cli {
     #[arg(
         short,
         long,
+        global = true,
         default_value = "config.toml",
         help = "Path to configuration file"
     )]
     config: PathBuf,
+    #[arg(
+        long,
+        global = true,
+        help = "Log the rendered webhook requests instead of sending them"
+    )]
+    dry_run: bool,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    #[command(about = "Monitor Discord presence and deliver webhooks (default)")]
+    Run {
+        #[arg(long, help = "Append received presences to this JSON lines file")]
+        record: Option<PathBuf>,
+    },
+    #[command(about = "Validate the configuration and print the resolved settings")]
+    Validate,
+    #[command(about = "Send a synthetic status change event to the configured webhook sinks")]
+    TestWebhook {
+        #[arg(long, help = "Only send to the sink with this name")]
+        sink: Option<String>,
+    },
+    #[command(about = "Replay recorded presences and print the events that would be emitted")]
+    Replay {
+        #[arg(help = "JSON lines file written by `run --record`")]
+        file: PathBuf,
+        #[arg(
+            long,
+            help = "Keep the virtual clock running until this RFC 3339 time after the last record"
+        )]
+        until: Option<DateTime<Utc>>,
+    },
 }
 
 #[tokio::main]
@@ -39,6 +93,116 @@ async fn main() -> anyhow::Result<()> {
     let settings = Settings::load_from_path(&cli.config)
         .with_context(|| format!("failed to load configuration from {}", cli.config.display()))?;
 
+    match cli.command.unwrap_or(Command::Run { record: None }) {
+        Command::Run { record } => start(cli.config, settings, cli.dry_run, record).await,
+        Command::Validate => {
+            println!("{} is valid", cli.config.display());
+            println!("{settings:#?}");
+            Ok(())
+        }
+        Command::TestWebhook { sink } => {
+            test_webhook(&settings, sink.as_deref(), cli.dry_run).await
+        }
+        Command::Replay { file, until } => {
+            let records = discord::read_records(&file)?;
+            let events = discord::replay(&settings, &records, until).await?;
+            for event in &events {
+                println!(
+                    "{}",
+                    serde_json::to_string(event).context("failed to serialize event")?
+                );
+            }
+            info!(
+                records = records.len(),
+                events = events.len(),
+                "replay finished"
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Sends one synthetic status change straight to the sinks, bypassing event
+/// kind filters, `[[rules]]`, quiet hours and the outbox.
+async fn test_webhook(
+    settings: &Settings,
+    only: Option<&str>,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    let sinks = webhook::build_sinks(
+        &settings.webhook,
+        &settings.message,
+        &settings.steam,
+        Arc::new(CacheService::default()),
+        dry_run,
+    )
+    .context("failed to setup webhook sinks")?;
+    let sinks: Vec<_> = sinks
+        .into_iter()
+        .filter(|sink| only.is_none_or(|name| sink.name == name))
+        .collect();
+    if sinks.is_empty() {
+        bail!("no webhook sink named {}", only.unwrap_or_default());
+    }
+
+    let target = settings
+        .discord
+        .targets()
+        .into_iter()
+        .next()
+        .context("no Discord target configured")?;
+    let event = DiscordStatusChangedEvent::new(
+        target.user_id,
+        target.guild_id,
+        Some(DiscordStatus::Offline),
+        DiscordStatus::Online,
+        Some(DiscordActivityContext {
+            name: "StatusHub test-webhook".to_string(),
+            details: Some("Synthetic event sent by statushub test-webhook".to_string()),
+            state: None,
+            steam_app_id: None,
+            started_at: None,
+            ends_at: None,
+        }),
+        None,
+    );
+
+    let mut failed = 0;
+    for sink in &sinks {
+        let started = Instant::now();
+        match sink.sender.send(&event).await {
+            Ok(()) if dry_run => println!("{}: rendered, not sent (dry run)", sink.name),
+            Ok(()) => println!(
+                "{}: delivered in {} ms",
+                sink.name,
+                started.elapsed().as_millis()
+            ),
+            Err(err) => {
+                failed += 1;
+                println!("{}: failed: {err:#}", sink.name);
+            }
+        }
+    }
+    if failed > 0 {
+        bail!("{failed} of {} webhook sinks failed", sinks.len());
+    }
+    Ok(())
+}
+
+/// Optional stores shared by the presence monitor and delivery.
+struct Stores {
+    state_cache: Option<Arc<PersistentStatusCache>>,
+    outbox: Option<Arc<Outbox>>,
+    history: Option<Arc<SessionHistory>>,
+    recorder: Option<Arc<PresenceRecorder>>,
+}
+
+async fn start(
+    config_path: PathBuf,
+    settings: Settings,
+    dry_run: bool,
+    record: Option<PathBuf>,
+) -> anyhow::Result<()> {
     let cache_service = Arc::new(
         CacheService::from_settings(&settings.cache)
             .await
@@ -63,38 +227,166 @@ async fn main() -> anyhow::Result<()> {
         None
     };
 
-    let sender = webhook::build_sender(
-        &settings.webhook,
-        &settings.message,
-        &settings.steam,
+    if dry_run {
+        info!("dry run: webhook requests are logged instead of sent, outbox disabled");
+    }
+    // A dry run must not consume events queued for real delivery.
+    let outbox = if settings.outbox.enabled && !dry_run {
+        Some(Arc::new(
+            Outbox::open(&settings.outbox).await.with_context(|| {
+                format!(
+                    "failed to initialize outbox at {}",
+                    settings.outbox.sqlite_path.display()
+                )
+            })?,
+        ))
+    } else {
+        None
+    };
+
+    let history = if settings.history.enabled {
+        Some(Arc::new(
+            SessionHistory::open(&settings.history)
+                .await
+                .with_context(|| {
+                    format!(
+                        "failed to initialize session history at {}",
+                        settings.history.sqlite_path.display()
+                    )
+                })?,
+        ))
+    } else {
+        None
+    };
+
+    let recorder = match record {
+        Some(path) => {
+            info!(path = %path.display(), "recording presences");
+            Some(Arc::new(PresenceRecorder::open(&path).await?))
+        }
+        None => None,
+    };
+
+    let pipeline = Pipeline::from_settings(&settings, cache_service.clone(), dry_run)?;
+    run(
+        config_path,
+        settings,
         cache_service,
+        pipeline,
+        Stores {
+            state_cache,
+            outbox,
+            history,
+            recorder,
+        },
     )
-    .context("failed to setup webhook sender")?;
-    run(settings, sender, state_cache).await
+    .await
 }
 
 async fn run(
+    config_path: PathBuf,
     settings: Settings,
-    sender: Arc<dyn WebhookSender>,
-    state_cache: Option<Arc<PersistentStatusCache>>,
+    cache_service: Arc<CacheService>,
+    pipeline: Pipeline,
+    stores: Stores,
 ) -> anyhow::Result<()> {
-    tokio::select! {
-        result = discord::run(settings.discord, settings.reminder, sender, state_cache) => {
+    let Stores {
+        state_cache,
+        outbox,
+        history,
+        recorder,
+    } = stores;
+    let (tx, rx) = mpsc::channel(256);
+    let hub = EventHub::new();
+    let (quiet_hours_tx, quiet_hours) = watch::channel(pipeline.quiet_hours.clone());
+    let (pipeline_tx, pipeline) = watch::channel(Arc::new(pipeline));
+    let delivery = tokio::spawn(delivery::run(rx, pipeline, outbox, hub.clone()));
+    let board = PresenceBoard::restore(
+        &settings.discord,
+        &settings.reminder,
+        state_cache.as_deref(),
+    )
+    .await?;
+
+    let reloader = tokio::spawn(
+        ConfigReloader {
+            path: config_path,
+            cache_service,
+            board: board.clone(),
+            pipeline: pipeline_tx,
+            quiet_hours: quiet_hours_tx,
+        }
+        .run(),
+    );
+
+    let digest = match history.clone() {
+        Some(history) if settings.digest.enabled => Some(tokio::spawn(digest::run(
+            settings.digest.clone(),
+            board.clone(),
+            history,
+            tx.clone(),
+        ))),
+        _ => None,
+    };
+
+    let heartbeat = history
+        .clone()
+        .map(|history| tokio::spawn(history::run_heartbeat(history)));
+
+    let http_settings = settings.http.clone();
+    let http_board = board.clone();
+    let http_server = async move {
+        if http_settings.enabled {
+            http::serve(http_settings, http_board, hub).await
+        } else {
+            std::future::pending().await
+        }
+    };
+
+    let result = tokio::select! {
+        result = discord::run(
+            settings.discord,
+            board,
+            tx,
+            state_cache,
+            history,
+            quiet_hours,
+            recorder,
+        ) => {
+            if let Some(digest) = &digest {
+                digest.abort();
+            }
+            let _ = delivery.await;
+            result
+        }
+        result = http_server => {
+            delivery.abort();
             result
         }
         _ = tokio::signal::ctrl_c() => {
             info!("received shutdown signal");
+            delivery.abort();
             Ok(())
         }
+    };
+    if let Some(digest) = digest {
+        digest.abort();
+    }
+    if let Some(heartbeat) = heartbeat {
+        heartbeat.abort();
     }
+    reloader.abort();
+    result
 }
 
 fn init_tracing() {
     let filter = EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| EnvFilter::new("statushub=info,serenity=warn"));
 
+    // Logs go to stderr so `validate`, `test-webhook` and `replay` output stays clean.
     tracing_subscriber::fmt()
         .compact()
+        .with_writer(std::io::stderr)
         .with_env_filter(filter)
         .with_target(false)
         .init();
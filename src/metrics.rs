@@ -0,0 +1,212 @@
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, Opts, Registry, TextEncoder,
+};
+
+static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+/// Process-wide Prometheus metrics, exposed by the HTTP API at `/metrics`.
+pub struct Metrics {
+    registry: Registry,
+    pub presence_updates: IntCounter,
+    pub events_emitted: IntCounterVec,
+    pub rule_matches: IntCounterVec,
+    pub webhook_deliveries: IntCounterVec,
+    pub webhook_delivery_seconds: HistogramVec,
+    pub sink_queue_drops: IntCounterVec,
+    pub steam_api_requests: IntCounterVec,
+    pub steam_cache_lookups: IntCounterVec,
+    pub cache_operation_seconds: HistogramVec,
+    pub reminders_fired: IntCounter,
+    pub gateway_reconnects: IntCounter,
+}
+
+pub fn metrics() -> &'static Metrics {
+    &METRICS
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new_custom(Some("statushub".to_string()), None)
+            .expect("metrics registry prefix is valid");
+
+        let presence_updates = IntCounter::new(
+            "presence_updates_total",
+            "Presence updates received for monitored targets",
+        )
+        .expect("metric definition is valid");
+        let events_emitted = IntCounterVec::new(
+            Opts::new("events_emitted_total", "Events produced, by kind"),
+            &["kind"],
+        )
+        .expect("metric definition is valid");
+        let rule_matches = IntCounterVec::new(
+            Opts::new(
+                "rule_matches_total",
+                "Events decided by a [[rules]] entry, by rule and action",
+            ),
+            &["rule", "action"],
+        )
+        .expect("metric definition is valid");
+        let webhook_deliveries = IntCounterVec::new(
+            Opts::new(
+                "webhook_deliveries_total",
+                "Webhook delivery attempts, by sink and outcome (success, failure, dry_run)",
+            ),
+            &["sink", "outcome"],
+        )
+        .expect("metric definition is valid");
+        let webhook_delivery_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "webhook_delivery_duration_seconds",
+                "Webhook delivery latency, by sink",
+            ),
+            &["sink"],
+        )
+        .expect("metric definition is valid");
+        let sink_queue_drops = IntCounterVec::new(
+            Opts::new(
+                "sink_queue_dropped_total",
+                "Events dropped because the sink's queue was full, by sink",
+            ),
+            &["sink"],
+        )
+        .expect("metric definition is valid");
+        let steam_api_requests = IntCounterVec::new(
+            Opts::new(
+                "steam_api_requests_total",
+                "Steam Web API requests, by endpoint and outcome",
+            ),
+            &["endpoint", "outcome"],
+        )
+        .expect("metric definition is valid");
+        let steam_cache_lookups = IntCounterVec::new(
+            Opts::new(
+                "steam_cache_lookups_total",
+                "Steam game detail cache lookups, by layer (memory, database) and result",
+            ),
+            &["layer", "result"],
+        )
+        .expect("metric definition is valid");
+        let cache_operation_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "cache_operation_duration_seconds",
+                "SQLite cache operation latency, by operation",
+            )
+            .buckets(vec![
+                0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0,
+            ]),
+            &["operation"],
+        )
+        .expect("metric definition is valid");
+        let reminders_fired = IntCounter::new("reminders_fired_total", "Reminder events fired")
+            .expect("metric definition is valid");
+        let gateway_reconnects = IntCounter::new(
+            "gateway_reconnects_total",
+            "Discord gateway reconnects and session resumes",
+        )
+        .expect("metric definition is valid");
+
+        for collector in [
+            Box::new(presence_updates.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(events_emitted.clone()),
+            Box::new(rule_matches.clone()),
+            Box::new(webhook_deliveries.clone()),
+            Box::new(webhook_delivery_seconds.clone()),
+            Box::new(sink_queue_drops.clone()),
+            Box::new(steam_api_requests.clone()),
+            Box::new(steam_cache_lookups.clone()),
+            Box::new(cache_operation_seconds.clone()),
+            Box::new(reminders_fired.clone()),
+            Box::new(gateway_reconnects.clone()),
+        ] {
+            registry
+                .register(collector)
+                .expect("metric names are unique");
+        }
+
+        Self {
+            registry,
+            presence_updates,
+            events_emitted,
+            rule_matches,
+            webhook_deliveries,
+            webhook_delivery_seconds,
+            sink_queue_drops,
+            steam_api_requests,
+            steam_cache_lookups,
+            cache_operation_seconds,
+            reminders_fired,
+            gateway_reconnects,
+        }
+    }
+
+    pub fn record_delivery(&self, sink: &str, success: bool, elapsed: Duration) {
+        let outcome = if success { "success" } else { "failure" };
+        self.webhook_deliveries
+            .with_label_values(&[sink, outcome])
+            .inc();
+        self.webhook_delivery_seconds
+            .with_label_values(&[sink])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    /// Counts a request rendered but not sent in `--dry-run`, outside the latency histogram.
+    pub fn record_dry_run(&self, sink: &str) {
+        self.webhook_deliveries
+            .with_label_values(&[sink, "dry_run"])
+            .inc();
+    }
+
+    pub fn record_steam_cache_lookup(&self, layer: &str, hit: bool) {
+        let result = if hit { "hit" } else { "miss" };
+        self.steam_cache_lookups
+            .with_label_values(&[layer, result])
+            .inc();
+    }
+
+    /// Renders every metric in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("text encoding into a Vec cannot fail");
+        String::from_utf8(buffer).expect("prometheus text output is UTF-8")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_includes_prefixed_metrics() {
+        let metrics = metrics();
+        metrics.presence_updates.inc();
+        metrics.record_delivery("default", true, Duration::from_millis(20));
+        metrics.record_dry_run("preview");
+        metrics.record_steam_cache_lookup("memory", false);
+
+        let text = metrics.render();
+        assert!(text.contains("statushub_presence_updates_total"));
+        assert!(
+            text.contains(
+                "statushub_webhook_deliveries_total{outcome=\"success\",sink=\"default\"}"
+            )
+        );
+        assert!(
+            text.contains(
+                "statushub_webhook_deliveries_total{outcome=\"dry_run\",sink=\"preview\"}"
+            )
+        );
+        assert!(
+            !text.contains("statushub_webhook_delivery_duration_seconds_count{sink=\"preview\"}")
+        );
+        assert!(text.contains("statushub_webhook_delivery_duration_seconds_bucket"));
+        assert!(
+            text.contains("statushub_steam_cache_lookups_total{layer=\"memory\",result=\"miss\"}")
+        );
+    }
+}
@@ -0,0 +1,445 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use rusqlite::{Connection, OptionalExtension, params};
+use tokio::task;
+
+use crate::config::OutboxSettings;
+use crate::event::DiscordStatusChangedEvent;
+
+const OUTBOX_TABLE_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS outbox_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    sink TEXT NOT NULL DEFAULT 'default',
+    payload TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at INTEGER NOT NULL,
+    last_error TEXT,
+    created_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS outbox_dead_letters (
+    id INTEGER PRIMARY KEY,
+    sink TEXT NOT NULL DEFAULT 'default',
+    payload TEXT NOT NULL,
+    attempts INTEGER NOT NULL,
+    last_error TEXT,
+    created_at INTEGER NOT NULL,
+    failed_at INTEGER NOT NULL
+);
+"#;
+
+/// Adds the `sink` column to databases created before sinks existed.
+const OUTBOX_SINK_MIGRATIONS: [(&str, &str); 2] = [
+    (
+        "outbox_events",
+        "ALTER TABLE outbox_events ADD COLUMN sink TEXT NOT NULL DEFAULT 'default'",
+    ),
+    (
+        "outbox_dead_letters",
+        "ALTER TABLE outbox_dead_letters ADD COLUMN sink TEXT NOT NULL DEFAULT 'default'",
+    ),
+];
+
+const OUTBOX_INDEX_SQL: &str =
+    "CREATE INDEX IF NOT EXISTS idx_outbox_events_sink_id ON outbox_events(sink, id);";
+
+/// Persistent queue of events waiting for webhook delivery.
+///
+/// Each sink has its own ordered queue. Entries are delivered strictly in insertion
+/// order per sink: the oldest entry is retried with exponential backoff until it
+/// succeeds or exhausts `max_attempts`, at which point it is moved to the
+/// dead-letter table. Deferred entries wait for their time without holding back
+/// the entries behind them.
+#[derive(Debug)]
+pub struct Outbox {
+    path: PathBuf,
+    max_attempts: u32,
+    initial_backoff_seconds: u64,
+    max_backoff_seconds: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct OutboxEntry {
+    pub id: i64,
+    pub event: DiscordStatusChangedEvent,
+    pub attempts: u32,
+    pub next_attempt_at: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureOutcome {
+    Retry { next_attempt_at: i64 },
+    DeadLettered,
+}
+
+impl Outbox {
+    pub async fn open(settings: &OutboxSettings) -> Result<Self> {
+        let outbox = Self {
+            path: settings.sqlite_path.clone(),
+            max_attempts: settings.max_attempts,
+            initial_backoff_seconds: settings.initial_backoff_seconds,
+            max_backoff_seconds: settings.max_backoff_seconds,
+        };
+
+        let path = outbox.path.clone();
+        task::spawn_blocking(move || -> Result<()> {
+            if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                fs::create_dir_all(parent).with_context(|| {
+                    format!(
+                        "failed to create outbox directory for sqlite database: {}",
+                        parent.display()
+                    )
+                })?;
+            }
+
+            let conn = open_connection(&path)?;
+            conn.execute_batch(OUTBOX_TABLE_SQL)
+                .context("failed to initialize sqlite outbox schema")?;
+            for (table, migration) in OUTBOX_SINK_MIGRATIONS {
+                if !has_column(&conn, table, "sink")? {
+                    conn.execute_batch(migration)
+                        .with_context(|| format!("failed to migrate outbox table {table}"))?;
+                }
+            }
+            conn.execute_batch(OUTBOX_INDEX_SQL)
+                .context("failed to initialize sqlite outbox index")?;
+            Ok(())
+        })
+        .await
+        .context("sqlite outbox init task join failed")??;
+
+        Ok(outbox)
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    pub async fn enqueue(&self, sink: &str, event: &DiscordStatusChangedEvent) -> Result<i64> {
+        self.insert(sink, event, None).await
+    }
+
+    /// Enqueues an event that must not be delivered before `deliver_at` (unix
+    /// seconds), such as one deferred by quiet hours.
+    pub async fn enqueue_deferred(
+        &self,
+        sink: &str,
+        event: &DiscordStatusChangedEvent,
+        deliver_at: i64,
+    ) -> Result<i64> {
+        self.insert(sink, event, Some(deliver_at)).await
+    }
+
+    async fn insert(
+        &self,
+        sink: &str,
+        event: &DiscordStatusChangedEvent,
+        deliver_at: Option<i64>,
+    ) -> Result<i64> {
+        let payload = serde_json::to_string(event).context("failed to serialize outbox event")?;
+        let sink = sink.to_string();
+        self.with_connection(move |conn| {
+            let now = now_unix_seconds();
+            conn.execute(
+                r#"
+                INSERT INTO outbox_events (sink, payload, attempts, next_attempt_at, created_at)
+                VALUES (?1, ?2, 0, ?3, ?4)
+                "#,
+                params![sink, payload, deliver_at.unwrap_or(now).max(now), now],
+            )
+            .context("failed to insert outbox event")?;
+            Ok(conn.last_insert_rowid())
+        })
+        .await
+    }
+
+    /// Returns the oldest pending entry of a sink, regardless of whether it is due
+    /// yet. Deferred entries that are not due yet (never attempted, due later) come
+    /// after every other entry, earliest first.
+    pub async fn peek(&self, sink: &str) -> Result<Option<OutboxEntry>> {
+        loop {
+            let sink = sink.to_string();
+            let row: Option<(i64, String, u32, i64)> = self
+                .with_connection(move |conn| {
+                    conn.query_row(
+                        r#"
+                        SELECT id, payload, attempts, next_attempt_at
+                        FROM outbox_events
+                        WHERE sink = ?1
+                        ORDER BY
+                            attempts = 0 AND next_attempt_at > ?2,
+                            CASE WHEN attempts = 0 AND next_attempt_at > ?2
+                                THEN next_attempt_at END,
+                            id ASC
+                        LIMIT 1
+                        "#,
+                        params![sink, now_unix_seconds()],
+                        |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)),
+                    )
+                    .optional()
+                    .context("failed to query outbox head")
+                })
+                .await?;
+
+            let Some((id, payload, attempts, next_attempt_at)) = row else {
+                return Ok(None);
+            };
+            match serde_json::from_str(&payload) {
+                Ok(event) => {
+                    return Ok(Some(OutboxEntry {
+                        id,
+                        event,
+                        attempts,
+                        next_attempt_at,
+                    }));
+                }
+                Err(err) => {
+                    // An undecodable payload can never be delivered; park it so it
+                    // does not block the rest of the queue.
+                    self.move_to_dead_letters(id, attempts, format!("invalid payload: {err}"))
+                        .await?;
+                }
+            }
+        }
+    }
+
+    pub async fn mark_delivered(&self, id: i64) -> Result<()> {
+        self.with_connection(move |conn| {
+            conn.execute("DELETE FROM outbox_events WHERE id = ?1", params![id])
+                .context("failed to delete delivered outbox event")?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn mark_failed(&self, entry: &OutboxEntry, error: &str) -> Result<FailureOutcome> {
+        let attempts = entry.attempts.saturating_add(1);
+        if attempts >= self.max_attempts {
+            self.move_to_dead_letters(entry.id, attempts, error.to_string())
+                .await?;
+            return Ok(FailureOutcome::DeadLettered);
+        }
+
+        let delay = backoff_seconds(
+            self.initial_backoff_seconds,
+            self.max_backoff_seconds,
+            attempts,
+        );
+        let next_attempt_at = now_unix_seconds().saturating_add(delay as i64);
+        let id = entry.id;
+        let error = error.to_string();
+        self.with_connection(move |conn| {
+            conn.execute(
+                r#"
+                UPDATE outbox_events
+                SET attempts = ?2, next_attempt_at = ?3, last_error = ?4
+                WHERE id = ?1
+                "#,
+                params![id, attempts, next_attempt_at, error],
+            )
+            .context("failed to update outbox retry state")?;
+            Ok(())
+        })
+        .await?;
+
+        Ok(FailureOutcome::Retry { next_attempt_at })
+    }
+
+    pub async fn pending_count(&self, sink: &str) -> Result<u64> {
+        let sink = sink.to_string();
+        self.with_connection(move |conn| {
+            conn.query_row(
+                "SELECT COUNT(*) FROM outbox_events WHERE sink = ?1",
+                params![sink],
+                |r| r.get(0),
+            )
+            .context("failed to count outbox events")
+        })
+        .await
+    }
+
+    async fn move_to_dead_letters(&self, id: i64, attempts: u32, error: String) -> Result<()> {
+        self.with_connection(move |conn| {
+            let tx = conn
+                .transaction()
+                .context("failed to start outbox transaction")?;
+            tx.execute(
+                r#"
+                INSERT INTO outbox_dead_letters (id, sink, payload, attempts, last_error, created_at, failed_at)
+                SELECT id, sink, payload, ?2, ?3, created_at, ?4 FROM outbox_events WHERE id = ?1
+                "#,
+                params![id, attempts, error, now_unix_seconds()],
+            )
+            .context("failed to insert outbox dead letter")?;
+            tx.execute("DELETE FROM outbox_events WHERE id = ?1", params![id])
+                .context("failed to delete dead-lettered outbox event")?;
+            tx.commit().context("failed to commit outbox dead letter")?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn with_connection<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> Result<T> + Send + 'static,
+    {
+        let path = self.path.clone();
+        task::spawn_blocking(move || {
+            let mut conn = open_connection(&path)?;
+            f(&mut conn)
+        })
+        .await
+        .context("sqlite outbox task join failed")?
+    }
+}
+
+/// Exponential backoff: `initial * 2^(attempts - 1)`, capped at `max`.
+pub fn backoff_seconds(initial: u64, max: u64, attempts: u32) -> u64 {
+    let exponent = attempts.saturating_sub(1).min(32);
+    initial.saturating_mul(1u64 << exponent).min(max)
+}
+
+pub fn now_unix_seconds() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() as i64)
+}
+
+fn has_column(conn: &Connection, table: &str, column: &str) -> Result<bool> {
+    let mut stmt = conn
+        .prepare(&format!("PRAGMA table_info({table})"))
+        .with_context(|| format!("failed to inspect outbox table {table}"))?;
+    let names = stmt
+        .query_map([], |r| r.get::<_, String>(1))
+        .with_context(|| format!("failed to read columns of outbox table {table}"))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(names.iter().any(|name| name == column))
+}
+
+fn open_connection(path: &Path) -> Result<Connection> {
+    Connection::open(path)
+        .with_context(|| format!("failed to open sqlite outbox DB at {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::DiscordStatus;
+
+    fn make_test_settings(file: &str, max_attempts: u32) -> OutboxSettings {
+        let unique = format!(
+            "statushub_test_{}_{}",
+            file,
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        );
+        OutboxSettings {
+            enabled: true,
+            sqlite_path: std::env::temp_dir().join(unique),
+            max_attempts,
+            initial_backoff_seconds: 5,
+            max_backoff_seconds: 60,
+        }
+    }
+
+    fn make_event(user_id: u64) -> DiscordStatusChangedEvent {
+        DiscordStatusChangedEvent::new(
+            user_id,
+            None,
+            Some(DiscordStatus::Offline),
+            DiscordStatus::Online,
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn backoff_grows_and_caps() {
+        assert_eq!(backoff_seconds(5, 60, 1), 5);
+        assert_eq!(backoff_seconds(5, 60, 2), 10);
+        assert_eq!(backoff_seconds(5, 60, 4), 40);
+        assert_eq!(backoff_seconds(5, 60, 5), 60);
+        assert_eq!(backoff_seconds(5, 60, 100), 60);
+    }
+
+    #[tokio::test]
+    async fn outbox_preserves_order_and_retries() {
+        let settings = make_test_settings("outbox.sqlite3", 3);
+        let outbox = Outbox::open(&settings)
+            .await
+            .expect("outbox init should succeed");
+
+        outbox.enqueue("a", &make_event(1)).await.expect("enqueue");
+        outbox.enqueue("b", &make_event(3)).await.expect("enqueue");
+        outbox.enqueue("a", &make_event(2)).await.expect("enqueue");
+
+        let head = outbox.peek("a").await.expect("peek").expect("head exists");
+        assert_eq!(head.event.user_id, 1);
+
+        let outcome = outbox.mark_failed(&head, "boom").await.expect("mark");
+        assert!(matches!(outcome, FailureOutcome::Retry { .. }));
+        let head = outbox.peek("a").await.expect("peek").expect("head exists");
+        assert_eq!(head.event.user_id, 1);
+        assert_eq!(head.attempts, 1);
+
+        outbox.mark_delivered(head.id).await.expect("deliver");
+        let head = outbox.peek("a").await.expect("peek").expect("head exists");
+        assert_eq!(head.event.user_id, 2);
+
+        let other = outbox.peek("b").await.expect("peek").expect("head exists");
+        assert_eq!(other.event.user_id, 3);
+
+        let _ = fs::remove_file(settings.sqlite_path);
+    }
+
+    #[tokio::test]
+    async fn deferred_entries_do_not_block_the_queue() {
+        let settings = make_test_settings("outbox_deferred.sqlite3", 3);
+        let outbox = Outbox::open(&settings)
+            .await
+            .expect("outbox init should succeed");
+        let later = now_unix_seconds() + 3_600;
+
+        outbox
+            .enqueue_deferred("a", &make_event(1), later + 60)
+            .await
+            .expect("enqueue");
+        outbox
+            .enqueue_deferred("a", &make_event(2), later)
+            .await
+            .expect("enqueue");
+        outbox.enqueue("a", &make_event(3)).await.expect("enqueue");
+
+        let head = outbox.peek("a").await.expect("peek").expect("head exists");
+        assert_eq!(head.event.user_id, 3);
+        outbox.mark_delivered(head.id).await.expect("deliver");
+
+        let head = outbox.peek("a").await.expect("peek").expect("head exists");
+        assert_eq!((head.event.user_id, head.next_attempt_at), (2, later));
+        assert_eq!(outbox.pending_count("a").await.expect("count"), 2);
+
+        let _ = fs::remove_file(settings.sqlite_path);
+    }
+
+    #[tokio::test]
+    async fn outbox_dead_letters_after_max_attempts() {
+        let settings = make_test_settings("outbox_dead.sqlite3", 2);
+        let outbox = Outbox::open(&settings)
+            .await
+            .expect("outbox init should succeed");
+        outbox.enqueue("a", &make_event(1)).await.expect("enqueue");
+
+        let head = outbox.peek("a").await.expect("peek").expect("head exists");
+        outbox.mark_failed(&head, "first").await.expect("mark");
+        let head = outbox.peek("a").await.expect("peek").expect("head exists");
+        let outcome = outbox.mark_failed(&head, "second").await.expect("mark");
+
+        assert_eq!(outcome, FailureOutcome::DeadLettered);
+        assert!(outbox.peek("a").await.expect("peek").is_none());
+        assert_eq!(outbox.pending_count("a").await.expect("count"), 0);
+
+        let _ = fs::remove_file(settings.sqlite_path);
+    }
+}
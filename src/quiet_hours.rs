@@ -0,0 +1,241 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Local, NaiveTime, TimeDelta, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+
+use crate::config::{QuietHoursAction, QuietHoursSettings};
+use crate::event::{DiscordStatusChangedEvent, EventKind};
+
+/// Weekly quiet windows in a fixed time zone, applied by the delivery pipeline
+/// and the reminder loops.
+#[derive(Debug)]
+pub struct QuietHours {
+    timezone: Option<Tz>,
+    action: QuietHoursAction,
+    kinds: Vec<EventKind>,
+    windows: Vec<QuietWindow>,
+}
+
+#[derive(Debug)]
+struct QuietWindow {
+    days: Vec<Weekday>,
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+/// What to do with an event right now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuietDecision {
+    Deliver,
+    Drop,
+    /// Hold the event until the given instant.
+    Defer(DateTime<Utc>),
+    /// Deliver, waking OpenClaw with `next-heartbeat`.
+    NextHeartbeat,
+}
+
+impl QuietHours {
+    /// Returns `None` when quiet hours are disabled.
+    pub fn new(settings: &QuietHoursSettings) -> Result<Option<Self>> {
+        if !settings.enabled {
+            return Ok(None);
+        }
+        let windows = settings
+            .windows
+            .iter()
+            .enumerate()
+            .map(|(index, window)| {
+                let (start, end) = window
+                    .time_range()
+                    .with_context(|| format!("invalid quiet_hours.windows[{index}].time"))?;
+                Ok(QuietWindow {
+                    days: window.days.clone(),
+                    start,
+                    end,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Some(Self {
+            timezone: settings.tz()?,
+            action: settings.action,
+            kinds: settings.kinds.clone(),
+            windows,
+        }))
+    }
+
+    pub fn decide(&self, event: &DiscordStatusChangedEvent, now: DateTime<Utc>) -> QuietDecision {
+        if !self.kinds.contains(&event.kind()) {
+            return QuietDecision::Deliver;
+        }
+        let Some(until) = self.quiet_until(now) else {
+            return QuietDecision::Deliver;
+        };
+        match self.action {
+            QuietHoursAction::Drop => QuietDecision::Drop,
+            QuietHoursAction::Defer => QuietDecision::Defer(self.quiet_period_end(until)),
+            QuietHoursAction::NextHeartbeat => QuietDecision::NextHeartbeat,
+        }
+    }
+
+    /// Whether reminders due at `now` should be skipped rather than fired. With
+    /// `next_heartbeat` they still fire; otherwise the next reminder after the
+    /// window reports the full elapsed time instead of a backlog being delivered.
+    pub fn suppresses_reminders(&self, now: DateTime<Utc>) -> bool {
+        self.action != QuietHoursAction::NextHeartbeat
+            && self.kinds.contains(&EventKind::Reminder)
+            && self.quiet_until(now).is_some()
+    }
+
+    /// Follows back-to-back windows from `until` to the end of the quiet period.
+    fn quiet_period_end(&self, mut until: DateTime<Utc>) -> DateTime<Utc> {
+        // Windows repeat weekly, so a week of them bounds the chain.
+        for _ in 0..self.windows.len() * 7 {
+            match self.quiet_until(until) {
+                Some(next) if next > until => until = next,
+                _ => break,
+            }
+        }
+        until
+    }
+
+    /// End of the quiet window containing `at`, or `None` outside quiet hours.
+    pub fn quiet_until(&self, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self.timezone {
+            Some(timezone) => self.quiet_until_in(at.with_timezone(&timezone)),
+            None => self.quiet_until_in(at.with_timezone(&Local)),
+        }
+    }
+
+    fn quiet_until_in<Z: TimeZone>(&self, at: DateTime<Z>) -> Option<DateTime<Utc>> {
+        let date = at.date_naive();
+        let time = at.time();
+        let yesterday = date.pred_opt()?;
+
+        self.windows.iter().find_map(|window| {
+            let end_date = if window.start < window.end {
+                (window.days.contains(&date.weekday()) && window.start <= time && time < window.end)
+                    .then_some(date)
+            } else if time >= window.start && window.days.contains(&date.weekday()) {
+                date.succ_opt()
+            } else if time < window.end && window.days.contains(&yesterday.weekday()) {
+                Some(date)
+            } else {
+                None
+            }?;
+
+            let end = end_date.and_time(window.end);
+            let timezone = at.timezone();
+            // A window ending inside a DST gap ends once the gap is over.
+            timezone
+                .from_local_datetime(&end)
+                .earliest()
+                .or_else(|| {
+                    timezone
+                        .from_local_datetime(&(end + TimeDelta::hours(1)))
+                        .earliest()
+                })
+                .map(|end| end.with_timezone(&Utc))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::QuietWindowSettings;
+    use crate::event::DiscordStatus;
+
+    fn quiet_hours(action: QuietHoursAction) -> QuietHours {
+        QuietHours::new(&QuietHoursSettings {
+            enabled: true,
+            timezone: Some("Asia/Shanghai".to_string()),
+            action,
+            kinds: EventKind::ALL.to_vec(),
+            windows: vec![
+                QuietWindowSettings {
+                    days: vec![Weekday::Fri],
+                    time: "23:00-07:00".to_string(),
+                },
+                QuietWindowSettings {
+                    days: vec![Weekday::Sun],
+                    time: "13:00-14:00".to_string(),
+                },
+            ],
+        })
+        .expect("quiet hours should build")
+        .expect("quiet hours are enabled")
+    }
+
+    fn shanghai(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        chrono_tz::Asia::Shanghai
+            .with_ymd_and_hms(2026, 2, day, hour, minute, 0)
+            .single()
+            .expect("Shanghai has no DST, so every local time is unambiguous")
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn windows_follow_weekday_and_wrap_past_midnight() {
+        let quiet = quiet_hours(QuietHoursAction::Defer);
+        // 2026-02-13 is a Friday.
+        assert_eq!(quiet.quiet_until(shanghai(13, 22, 59)), None);
+        assert_eq!(
+            quiet.quiet_until(shanghai(13, 23, 0)),
+            Some(shanghai(14, 7, 0))
+        );
+        assert_eq!(
+            quiet.quiet_until(shanghai(14, 3, 0)),
+            Some(shanghai(14, 7, 0))
+        );
+        assert_eq!(quiet.quiet_until(shanghai(14, 7, 0)), None);
+        // Saturday night is not quiet, Sunday lunch is.
+        assert_eq!(quiet.quiet_until(shanghai(14, 23, 30)), None);
+        assert_eq!(
+            quiet.quiet_until(shanghai(15, 13, 30)),
+            Some(shanghai(15, 14, 0))
+        );
+    }
+
+    #[test]
+    fn decision_depends_on_action_and_kind() {
+        let event = DiscordStatusChangedEvent::new(
+            42,
+            None,
+            Some(DiscordStatus::Online),
+            DiscordStatus::Idle,
+            None,
+            None,
+        );
+        let night = shanghai(14, 3, 0);
+
+        assert_eq!(
+            quiet_hours(QuietHoursAction::Defer).decide(&event, night),
+            QuietDecision::Defer(shanghai(14, 7, 0))
+        );
+        assert_eq!(
+            quiet_hours(QuietHoursAction::Drop).decide(&event, night),
+            QuietDecision::Drop
+        );
+        let next_heartbeat = quiet_hours(QuietHoursAction::NextHeartbeat);
+        assert_eq!(
+            next_heartbeat.decide(&event, night),
+            QuietDecision::NextHeartbeat
+        );
+        assert!(!next_heartbeat.suppresses_reminders(night));
+        assert!(quiet_hours(QuietHoursAction::Drop).suppresses_reminders(night));
+
+        let mut back_to_back = quiet_hours(QuietHoursAction::Defer);
+        back_to_back.windows.push(QuietWindow {
+            days: vec![Weekday::Sat],
+            start: NaiveTime::from_hms_opt(7, 0, 0).expect("time is valid"),
+            end: NaiveTime::from_hms_opt(8, 0, 0).expect("time is valid"),
+        });
+        assert_eq!(
+            back_to_back.decide(&event, night),
+            QuietDecision::Defer(shanghai(14, 8, 0))
+        );
+
+        let mut reminders_only = quiet_hours(QuietHoursAction::Drop);
+        reminders_only.kinds = vec![EventKind::Reminder];
+        assert_eq!(reminders_only.decide(&event, night), QuietDecision::Deliver);
+    }
+}
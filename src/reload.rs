@@ -0,0 +1,171 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use anyhow::Result;
+use tokio::sync::{Notify, watch};
+use tokio::time::MissedTickBehavior;
+use tracing::{error, info};
+
+use crate::cache::CacheService;
+use crate::config::Settings;
+use crate::delivery::Pipeline;
+use crate::discord::PresenceBoard;
+use crate::quiet_hours::QuietHours;
+
+const CONFIG_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Reloads the configuration file when it changes or on SIGHUP.
+///
+/// Webhook sinks (senders and message templates), `[[rules]]`, quiet hours and
+/// reminder settings are replaced in place, so the Discord gateway stays connected
+/// and runtime presence state is kept. Other sections take effect after a restart.
+/// A configuration that fails to load or validate is logged and ignored.
+pub struct ConfigReloader {
+    pub path: PathBuf,
+    pub cache_service: Arc<CacheService>,
+    pub board: PresenceBoard,
+    pub pipeline: watch::Sender<Arc<Pipeline>>,
+    pub quiet_hours: watch::Sender<Option<Arc<QuietHours>>>,
+}
+
+impl ConfigReloader {
+    pub async fn run(self) {
+        let hangup = Arc::new(Notify::new());
+        #[cfg(unix)]
+        tokio::spawn(forward_hangup(hangup.clone()));
+
+        let mut last_modified = modified_at(&self.path).await;
+        let mut ticker = tokio::time::interval(CONFIG_POLL_INTERVAL);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+        loop {
+            tokio::select! {
+                _ = hangup.notified() => {
+                    info!(path = %self.path.display(), "received SIGHUP, reloading configuration");
+                }
+                _ = ticker.tick() => {
+                    let modified = modified_at(&self.path).await;
+                    // A missing file is usually an editor replacing it; wait for the new one.
+                    if modified.is_none() || modified == last_modified {
+                        continue;
+                    }
+                    last_modified = modified;
+                    info!(path = %self.path.display(), "configuration file changed, reloading");
+                }
+            }
+            match self.reload().await {
+                Ok(()) => info!(path = %self.path.display(), "configuration reloaded"),
+                Err(err) => error!(
+                    path = %self.path.display(),
+                    error = ?err,
+                    "configuration reload failed, keeping the running configuration"
+                ),
+            }
+        }
+    }
+
+    async fn reload(&self) -> Result<()> {
+        let settings = Settings::load_from_path(&self.path)?;
+        let dry_run = self.pipeline.borrow().dry_run;
+        let pipeline = Pipeline::from_settings(&settings, self.cache_service.clone(), dry_run)?;
+        self.board
+            .reload_reminders(&settings.discord, &settings.reminder)
+            .await?;
+        self.quiet_hours.send_replace(pipeline.quiet_hours.clone());
+        self.pipeline.send_replace(Arc::new(pipeline));
+        Ok(())
+    }
+}
+
+async fn modified_at(path: &PathBuf) -> Option<(SystemTime, u64)> {
+    let metadata = tokio::fs::metadata(path).await.ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+#[cfg(unix)]
+async fn forward_hangup(hangup: Arc<Notify>) {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    let mut signal = match signal(SignalKind::hangup()) {
+        Ok(signal) => signal,
+        Err(err) => {
+            tracing::warn!(error = ?err, "failed to listen for SIGHUP");
+            return;
+        }
+    };
+    while signal.recv().await.is_some() {
+        hangup.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+        [discord]
+        bot_token = "discord-token"
+        user_id = 1
+
+        [webhook]
+        url = "http://127.0.0.1:18789/hooks/wake"
+    "#;
+
+    #[tokio::test]
+    async fn invalid_config_keeps_running_pipeline() {
+        let path = std::env::temp_dir().join(format!(
+            "statushub_reload_{}.toml",
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        std::fs::write(&path, CONFIG).expect("config should be written");
+        let settings = Settings::load_from_path(&path).expect("config should load");
+        let cache_service = Arc::new(CacheService::default());
+        let board = PresenceBoard::restore(&settings.discord, &settings.reminder, None)
+            .await
+            .expect("board should restore");
+        let pipeline = Pipeline::from_settings(&settings, cache_service.clone(), false)
+            .expect("pipeline should build");
+        let (quiet_hours, _) = watch::channel(None);
+        let (pipeline, pipeline_rx) = watch::channel(Arc::new(pipeline));
+        let reloader = ConfigReloader {
+            path: path.clone(),
+            cache_service,
+            board,
+            pipeline,
+            quiet_hours,
+        };
+
+        std::fs::write(&path, CONFIG.replace("user_id = 1", "user_id = 0"))
+            .expect("config should be written");
+        assert!(reloader.reload().await.is_err());
+        assert!(!pipeline_rx.has_changed().expect("sender alive"));
+
+        let routed = r#"
+            [discord]
+            bot_token = "discord-token"
+            user_id = 1
+
+            [[webhook.sinks]]
+            name = "wake"
+            url = "http://127.0.0.1:18789/hooks/wake"
+
+            [[webhook.sinks]]
+            name = "log"
+            mode = "generic_json"
+            url = "http://127.0.0.1:9000/statushub"
+        "#;
+        std::fs::write(&path, routed).expect("config should be written");
+        reloader.reload().await.expect("reload should succeed");
+        assert!(pipeline_rx.has_changed().expect("sender alive"));
+        let names: Vec<_> = pipeline_rx
+            .borrow()
+            .sinks
+            .iter()
+            .map(|sink| sink.name.clone())
+            .collect();
+        assert_eq!(names, ["wake", "log"]);
+
+        let _ = std::fs::remove_file(path);
+    }
+}
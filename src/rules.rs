@@ -0,0 +1,319 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveTime, Utc};
+use chrono_tz::Tz;
+use regex::Regex;
+
+use crate::config::{RuleAction, RuleSettings};
+use crate::event::{DiscordStatus, DiscordStatusChangedEvent, EventKind};
+
+/// The `[[rules]]` filter stage between event producers and webhook sinks.
+///
+/// Rules are checked in order and the first one whose conditions all match
+/// decides: `allow` delivers as usual, `drop` delivers nowhere and `route`
+/// delivers only to the listed sinks. Events matching no rule are delivered as
+/// usual.
+#[derive(Debug, Default)]
+pub struct RuleSet {
+    rules: Vec<Rule>,
+}
+
+/// The rule that decided an event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RuleMatch<'a> {
+    pub rule: &'a str,
+    pub action: RuleAction,
+    pub sinks: &'a [String],
+}
+
+impl RuleMatch<'_> {
+    pub fn action_label(&self) -> &'static str {
+        match self.action {
+            RuleAction::Allow => "allow",
+            RuleAction::Drop => "drop",
+            RuleAction::Route => "route",
+        }
+    }
+
+    /// Whether the event goes to the sink named `sink` (before the sink's own
+    /// event kind filter).
+    pub fn delivers_to(&self, sink: &str) -> bool {
+        match self.action {
+            RuleAction::Allow => true,
+            RuleAction::Drop => false,
+            RuleAction::Route => self.sinks.iter().any(|name| name == sink),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Rule {
+    name: String,
+    action: RuleAction,
+    sinks: Vec<String>,
+    kinds: Vec<EventKind>,
+    user_ids: Vec<u64>,
+    from_status: Vec<DiscordStatus>,
+    to_status: Vec<DiscordStatus>,
+    activity: Option<Regex>,
+    steam_app_ids: Vec<u32>,
+    time: Option<(NaiveTime, NaiveTime)>,
+    timezone: Option<Tz>,
+}
+
+impl RuleSet {
+    pub fn new(settings: &[RuleSettings]) -> Result<Self> {
+        let rules = settings
+            .iter()
+            .enumerate()
+            .map(|(index, rule)| {
+                let field = format!("rules[{index}]");
+                Ok(Rule {
+                    name: rule.name.clone().unwrap_or_else(|| field.clone()),
+                    action: rule.action,
+                    sinks: rule.sinks.clone(),
+                    kinds: rule.kinds.clone(),
+                    user_ids: rule.user_ids.clone(),
+                    from_status: rule.from_status.clone(),
+                    to_status: rule.to_status.clone(),
+                    activity: rule
+                        .activity_regex()
+                        .with_context(|| format!("invalid {field}.activity"))?,
+                    steam_app_ids: rule.steam_app_ids.clone(),
+                    time: rule
+                        .time_range()
+                        .with_context(|| format!("invalid {field}.time"))?,
+                    timezone: rule
+                        .tz()
+                        .with_context(|| format!("invalid {field}.timezone"))?,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { rules })
+    }
+
+    /// Returns the first rule matching the event, judging time of day by the
+    /// time at which the event was observed in each rule's time zone.
+    pub fn evaluate(&self, event: &DiscordStatusChangedEvent) -> Option<RuleMatch<'_>> {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(event))
+            .map(|rule| RuleMatch {
+                rule: &rule.name,
+                action: rule.action,
+                sinks: &rule.sinks,
+            })
+    }
+}
+
+impl Rule {
+    fn matches(&self, event: &DiscordStatusChangedEvent) -> bool {
+        if !self.kinds.is_empty() && !self.kinds.contains(&event.kind()) {
+            return false;
+        }
+        if !self.user_ids.is_empty() && !self.user_ids.contains(&event.user_id) {
+            return false;
+        }
+        if !self.from_status.is_empty()
+            && !event
+                .previous_status
+                .is_some_and(|status| self.from_status.contains(&status))
+        {
+            return false;
+        }
+        if !self.to_status.is_empty() && !self.to_status.contains(&event.current_status) {
+            return false;
+        }
+        if let Some(activity) = &self.activity
+            && !activity_names(event).any(|name| activity.is_match(name))
+        {
+            return false;
+        }
+        if !self.steam_app_ids.is_empty()
+            && !steam_app_ids(event).any(|app_id| self.steam_app_ids.contains(&app_id))
+        {
+            return false;
+        }
+        if let Some((start, end)) = self.time
+            && !time_in_range(self.time_of_day(event.observed_at), start, end)
+        {
+            return false;
+        }
+        true
+    }
+
+    fn time_of_day(&self, at: DateTime<Utc>) -> NaiveTime {
+        match self.timezone {
+            Some(timezone) => at.with_timezone(&timezone).time(),
+            None => at.with_timezone(&Local).time(),
+        }
+    }
+}
+
+fn activity_names(event: &DiscordStatusChangedEvent) -> impl Iterator<Item = &str> {
+    event
+        .activity
+        .iter()
+        .map(|activity| activity.name.as_str())
+        .chain(
+            event
+                .activities
+                .iter()
+                .map(|activity| activity.name.as_str()),
+        )
+}
+
+fn steam_app_ids(event: &DiscordStatusChangedEvent) -> impl Iterator<Item = u32> + '_ {
+    event
+        .activity
+        .iter()
+        .filter_map(|activity| activity.steam_app_id)
+        .chain(
+            event
+                .activities
+                .iter()
+                .filter_map(|activity| activity.steam_app_id),
+        )
+}
+
+/// Whether `time` falls in `[start, end)`, wrapping past midnight when
+/// `end <= start`.
+pub fn time_in_range(time: NaiveTime, start: NaiveTime, end: NaiveTime) -> bool {
+    if start < end {
+        start <= time && time < end
+    } else {
+        time >= start || time < end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::DiscordActivityContext;
+
+    fn rules(raw: &str) -> RuleSet {
+        #[derive(serde::Deserialize)]
+        struct Rules {
+            rules: Vec<RuleSettings>,
+        }
+        let parsed: Rules = toml::from_str(raw).expect("rules should parse");
+        RuleSet::new(&parsed.rules).expect("rules should compile")
+    }
+
+    fn at(raw: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(raw)
+            .expect("valid timestamp")
+            .with_timezone(&Utc)
+    }
+
+    fn playing(name: &str, steam_app_id: Option<u32>) -> DiscordStatusChangedEvent {
+        DiscordStatusChangedEvent::new(
+            42,
+            None,
+            Some(DiscordStatus::Online),
+            DiscordStatus::Online,
+            Some(DiscordActivityContext {
+                name: name.to_string(),
+                details: None,
+                state: None,
+                steam_app_id,
+                started_at: None,
+                ends_at: None,
+            }),
+            None,
+        )
+    }
+
+    #[test]
+    fn first_matching_rule_decides() {
+        let rules = rules(
+            r#"
+            [[rules]]
+            name = "keep dota"
+            action = "allow"
+            steam_app_ids = [570]
+
+            [[rules]]
+            name = "no editor noise"
+            action = "drop"
+            kinds = ["activity_change"]
+            activity = "(?i)visual studio code"
+
+            [[rules]]
+            action = "route"
+            sinks = ["games"]
+            activity = ".*"
+            "#,
+        );
+
+        assert_eq!(
+            rules
+                .evaluate(&playing("Dota 2", Some(570)))
+                .map(|m| (m.rule, m.action)),
+            Some(("keep dota", RuleAction::Allow))
+        );
+        assert_eq!(
+            rules
+                .evaluate(&playing("Visual Studio Code", None))
+                .map(|m| m.action),
+            Some(RuleAction::Drop)
+        );
+        let routed = rules
+            .evaluate(&playing("Cyberpunk 2077", Some(1091500)))
+            .expect("route rule should match");
+        assert_eq!(routed.rule, "rules[2]");
+        assert!(routed.delivers_to("games"));
+        assert!(!routed.delivers_to("default"));
+    }
+
+    #[test]
+    fn status_transition_and_time_of_day() {
+        let rules = rules(
+            r#"
+            [[rules]]
+            action = "drop"
+            from_status = ["online", "dnd"]
+            to_status = ["idle"]
+            time = "22:00-07:00"
+            timezone = "UTC"
+            "#,
+        );
+        let idle = |observed_at: &str| {
+            DiscordStatusChangedEvent::new(
+                42,
+                None,
+                Some(DiscordStatus::Online),
+                DiscordStatus::Idle,
+                None,
+                None,
+            )
+            .with_observed_at(at(observed_at))
+        };
+        assert!(rules.evaluate(&idle("2026-10-15T23:30:00Z")).is_some());
+        assert!(rules.evaluate(&idle("2026-10-15T06:59:00Z")).is_some());
+        assert!(rules.evaluate(&idle("2026-10-15T07:00:00Z")).is_none());
+
+        let initial =
+            DiscordStatusChangedEvent::new(42, None, None, DiscordStatus::Idle, None, None)
+                .with_observed_at(at("2026-10-15T23:30:00Z"));
+        assert!(rules.evaluate(&initial).is_none());
+    }
+
+    #[test]
+    fn time_of_day_follows_the_rule_timezone() {
+        let rules = rules(
+            r#"
+            [[rules]]
+            action = "drop"
+            time = "23:00-07:00"
+            timezone = "Asia/Shanghai"
+            "#,
+        );
+        let event = |observed_at: &str| {
+            DiscordStatusChangedEvent::new(42, None, None, DiscordStatus::Idle, None, None)
+                .with_observed_at(at(observed_at))
+        };
+        // 15:30 UTC is 23:30 in Shanghai.
+        assert!(rules.evaluate(&event("2026-10-15T15:30:00Z")).is_some());
+        assert!(rules.evaluate(&event("2026-10-15T23:30:00Z")).is_none());
+    }
+}
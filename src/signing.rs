@@ -0,0 +1,183 @@
+//! HMAC-SHA256 webhook signatures.
+//!
+//! StatusHub signs the string `"{timestamp}.{body}"` with the shared secret, where
+//! `timestamp` is the Unix time in seconds sent in the timestamp header and `body`
+//! is the exact request body. The signature header carries `sha256=<hex digest>`.
+//! Receivers should recompute the digest over the raw body and reject requests
+//! whose timestamp is outside their tolerance window to prevent replays.
+
+use std::fmt::{Display, Formatter};
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+pub const DEFAULT_SIGNATURE_HEADER: &str = "x-statushub-signature";
+pub const DEFAULT_TIMESTAMP_HEADER: &str = "x-statushub-timestamp";
+pub const SIGNATURE_PREFIX: &str = "sha256=";
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureError {
+    InvalidTimestamp,
+    Expired,
+    MalformedSignature,
+    Mismatch,
+}
+
+impl Display for SignatureError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidTimestamp => write!(f, "signature timestamp is not a valid integer"),
+            Self::Expired => write!(f, "signature timestamp is outside the tolerance window"),
+            Self::MalformedSignature => write!(f, "signature header is malformed"),
+            Self::Mismatch => write!(f, "signature does not match the request body"),
+        }
+    }
+}
+
+impl std::error::Error for SignatureError {}
+
+/// Returns the signature header value (`sha256=<hex>`) for a request body.
+pub fn sign(secret: &[u8], timestamp: i64, body: &[u8]) -> String {
+    let digest = signed_payload_mac(secret, timestamp, body)
+        .finalize()
+        .into_bytes();
+    format!("{SIGNATURE_PREFIX}{}", encode_hex(&digest))
+}
+
+/// Verifies a request signed by StatusHub.
+///
+/// `timestamp` and `signature` are the raw header values, `body` the exact request
+/// body bytes and `now` the receiver's current Unix time in seconds. Requests whose
+/// timestamp differs from `now` by more than `tolerance_seconds` are rejected.
+pub fn verify(
+    secret: &[u8],
+    timestamp: &str,
+    signature: &str,
+    body: &[u8],
+    now: i64,
+    tolerance_seconds: u64,
+) -> Result<(), SignatureError> {
+    let timestamp: i64 = timestamp
+        .trim()
+        .parse()
+        .map_err(|_| SignatureError::InvalidTimestamp)?;
+    if now.abs_diff(timestamp) > tolerance_seconds {
+        return Err(SignatureError::Expired);
+    }
+
+    let expected = signature
+        .trim()
+        .strip_prefix(SIGNATURE_PREFIX)
+        .and_then(decode_hex)
+        .ok_or(SignatureError::MalformedSignature)?;
+
+    signed_payload_mac(secret, timestamp, body)
+        .verify_slice(&expected)
+        .map_err(|_| SignatureError::Mismatch)
+}
+
+fn signed_payload_mac(secret: &[u8], timestamp: i64, body: &[u8]) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut out = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        out.push(DIGITS[(byte >> 4) as usize] as char);
+        out.push(DIGITS[(byte & 0x0f) as usize] as char);
+    }
+    out
+}
+
+fn decode_hex(raw: &str) -> Option<Vec<u8>> {
+    if !raw.len().is_multiple_of(2) {
+        return None;
+    }
+    raw.as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            let high = (pair[0] as char).to_digit(16)?;
+            let low = (pair[1] as char).to_digit(16)?;
+            Some((high * 16 + low) as u8)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sign_matches_known_vector() {
+        // echo -n '1700000000.{"a":1}' | openssl dgst -sha256 -hmac secret
+        assert_eq!(
+            sign(b"secret", 1_700_000_000, br#"{"a":1}"#),
+            "sha256=49f24e537407743fa4a0242bb63b94b9a47ee99cbbe071ccd8a22550ae411686"
+        );
+    }
+
+    #[test]
+    fn verify_roundtrip() {
+        let body = br#"{"text":"hello","mode":"now"}"#;
+        let signature = sign(b"secret", 1_700_000_000, body);
+        assert_eq!(
+            verify(
+                b"secret",
+                "1700000000",
+                &signature,
+                body,
+                1_700_000_030,
+                300
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn verify_rejects_tampering_and_replays() {
+        let body = br#"{"text":"hello"}"#;
+        let signature = sign(b"secret", 1_700_000_000, body);
+
+        assert_eq!(
+            verify(
+                b"secret",
+                "1700000000",
+                &signature,
+                b"{}",
+                1_700_000_000,
+                300
+            ),
+            Err(SignatureError::Mismatch)
+        );
+        assert_eq!(
+            verify(b"other", "1700000000", &signature, body, 1_700_000_000, 300),
+            Err(SignatureError::Mismatch)
+        );
+        assert_eq!(
+            verify(
+                b"secret",
+                "1700000000",
+                &signature,
+                body,
+                1_700_001_000,
+                300
+            ),
+            Err(SignatureError::Expired)
+        );
+        assert_eq!(
+            verify(b"secret", "1700000000", "md5=abc", body, 1_700_000_000, 300),
+            Err(SignatureError::MalformedSignature)
+        );
+        assert_eq!(
+            verify(b"secret", "soon", &signature, body, 1_700_000_000, 300),
+            Err(SignatureError::InvalidTimestamp)
+        );
+    }
+}
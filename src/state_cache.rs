@@ -82,13 +82,12 @@ impl PersistentStatusCache {
             .await
             .context("status cache write task join failed")??;
 
-        if let Some(cache_service) = self.cache_service.as_ref() {
-            if let Err(err) = cache_service
+        if let Some(cache_service) = self.cache_service.as_ref()
+            && let Err(err) = cache_service
                 .set_json(STATUS_CACHE_NAMESPACE, &key, &status, None)
                 .await
-            {
-                warn!(error = ?err, key, "failed to write status to DB cache");
-            }
+        {
+            warn!(error = ?err, key, "failed to write status to DB cache");
         }
 
         Ok(())
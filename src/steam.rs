@@ -10,6 +10,7 @@ use tracing::warn;
 
 use crate::cache::CacheService;
 use crate::config::SteamSettings;
+use crate::metrics::metrics;
 
 const STEAM_GAME_DETAILS_NAMESPACE: &str = "steam.game_details";
 
@@ -50,7 +51,10 @@ impl SteamClient {
 
         Ok(Self {
             client,
-            api_key: settings.api_key.clone(),
+            api_key: settings
+                .api_key
+                .as_ref()
+                .map(|key| key.expose().to_string()),
             language: settings.language.clone(),
             description_max_chars: settings.description_max_chars,
             db_cache_ttl_seconds: settings.db_cache_ttl_seconds,
@@ -62,13 +66,19 @@ impl SteamClient {
     }
 
     pub async fn fetch_game_details(&self, app_id: u32) -> Result<Option<SteamGameDetails>> {
-        if let Some(cached) = self.get_from_memory_cache(app_id).await {
+        let memory_cached = self.get_from_memory_cache(app_id).await;
+        metrics().record_steam_cache_lookup("memory", memory_cached.is_some());
+        if let Some(cached) = memory_cached {
             return Ok(Some(cached));
         }
 
-        if let Some(cached) = self.get_from_database_cache(app_id).await {
-            self.put_to_memory_cache(app_id, cached.clone()).await;
-            return Ok(Some(cached));
+        if self.cache_service.is_some() {
+            let database_cached = self.get_from_database_cache(app_id).await;
+            metrics().record_steam_cache_lookup("database", database_cached.is_some());
+            if let Some(cached) = database_cached {
+                self.put_to_memory_cache(app_id, cached.clone()).await;
+                return Ok(Some(cached));
+            }
         }
 
         let fetched = self.fetch_game_details_from_api(app_id).await?;
@@ -87,17 +97,22 @@ impl SteamClient {
             .append_pair("appids", &app_id.to_string())
             .append_pair("l", &self.language);
 
-        let response: HashMap<String, AppDetailsEnvelope> = self
-            .client
-            .get(url)
-            .send()
-            .await
-            .context("failed to query Steam appdetails API")?
-            .error_for_status()
-            .context("Steam appdetails API returned an error status")?
-            .json()
-            .await
-            .context("failed to parse Steam appdetails response")?;
+        let response: HashMap<String, AppDetailsEnvelope> = record_api_request(
+            "appdetails",
+            async {
+                self.client
+                    .get(url)
+                    .send()
+                    .await
+                    .context("failed to query Steam appdetails API")?
+                    .error_for_status()
+                    .context("Steam appdetails API returned an error status")?
+                    .json()
+                    .await
+                    .context("failed to parse Steam appdetails response")
+            }
+            .await,
+        )?;
 
         let key = app_id.to_string();
         let Some(entry) = response.get(&key) else {
@@ -139,17 +154,22 @@ impl SteamClient {
             .append_pair("key", api_key)
             .append_pair("appid", &app_id.to_string());
 
-        let response: CurrentPlayersRoot = self
-            .client
-            .get(url)
-            .send()
-            .await
-            .context("failed to query Steam current players API")?
-            .error_for_status()
-            .context("Steam current players API returned an error status")?
-            .json()
-            .await
-            .context("failed to parse Steam current players response")?;
+        let response: CurrentPlayersRoot = record_api_request(
+            "current_players",
+            async {
+                self.client
+                    .get(url)
+                    .send()
+                    .await
+                    .context("failed to query Steam current players API")?
+                    .error_for_status()
+                    .context("Steam current players API returned an error status")?
+                    .json()
+                    .await
+                    .context("failed to parse Steam current players response")
+            }
+            .await,
+        )?;
 
         Ok(response.response.player_count)
     }
@@ -168,10 +188,11 @@ impl SteamClient {
         let mut cache = self.memory_cache.write().await;
         cache.retain(|_, entry| entry.expires_at > Instant::now());
 
-        if cache.len() >= self.memory_cache_capacity && !cache.contains_key(&app_id) {
-            if let Some(evict_key) = cache.keys().next().copied() {
-                cache.remove(&evict_key);
-            }
+        if cache.len() >= self.memory_cache_capacity
+            && !cache.contains_key(&app_id)
+            && let Some(evict_key) = cache.keys().next().copied()
+        {
+            cache.remove(&evict_key);
         }
 
         cache.insert(
@@ -241,6 +262,15 @@ struct CurrentPlayersEnvelope {
     player_count: Option<u32>,
 }
 
+fn record_api_request<T>(endpoint: &str, result: Result<T>) -> Result<T> {
+    let outcome = if result.is_ok() { "success" } else { "failure" };
+    metrics()
+        .steam_api_requests
+        .with_label_values(&[endpoint, outcome])
+        .inc();
+    result
+}
+
 fn non_empty_trimmed(value: &str) -> Option<&str> {
     let trimmed = value.trim();
     if trimmed.is_empty() {
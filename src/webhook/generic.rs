@@ -18,14 +18,14 @@ impl GenericJsonSender {
 #[async_trait]
 impl WebhookSender for GenericJsonSender {
     async fn send(&self, event: &DiscordStatusChangedEvent) -> Result<()> {
-        let response = self
+        let Some(response) = self
             .client
-            .client
-            .post(self.client.url.clone())
-            .json(event)
-            .send()
+            .post_json(event)
             .await
-            .context("failed to call generic webhook")?;
+            .context("failed to call generic webhook")?
+        else {
+            return Ok(());
+        };
 
         if response.status().is_success() {
             return Ok(());
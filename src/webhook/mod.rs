@@ -1,16 +1,25 @@
 mod generic;
 mod openclaw;
+mod template;
 
 use std::sync::Arc;
 
 use anyhow::{Context, Result};
 use async_trait::async_trait;
-use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderName, HeaderValue};
-use reqwest::{Client, Url};
+use chrono::Utc;
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Client, Request, Response, Url};
+use serde::Serialize;
+use statushub::signing;
+use tracing::info;
 
 use crate::cache::CacheService;
-use crate::config::{MessageTemplateSettings, SteamSettings, WebhookMode, WebhookSettings};
-use crate::event::DiscordStatusChangedEvent;
+use crate::config::{
+    MessageTemplateSettings, Secret, SteamSettings, WebhookMode, WebhookSettings,
+    WebhookSinkSettings,
+};
+use crate::event::{DiscordStatusChangedEvent, EventKind};
+use crate::steam::SteamClient;
 use crate::webhook::generic::GenericJsonSender;
 use crate::webhook::openclaw::OpenClawWakeSender;
 
@@ -19,21 +28,63 @@ pub trait WebhookSender: Send + Sync {
     async fn send(&self, event: &DiscordStatusChangedEvent) -> Result<()>;
 }
 
-pub fn build_sender(
+/// A named webhook destination together with the event kinds routed to it.
+#[derive(Clone)]
+pub struct WebhookSink {
+    pub name: String,
+    pub events: Vec<EventKind>,
+    pub sender: Arc<dyn WebhookSender>,
+}
+
+impl WebhookSink {
+    pub fn accepts(&self, kind: EventKind) -> bool {
+        self.events.contains(&kind)
+    }
+}
+
+pub fn build_sinks(
     settings: &WebhookSettings,
     message: &MessageTemplateSettings,
     steam: &SteamSettings,
     cache_service: Arc<CacheService>,
+    dry_run: bool,
+) -> Result<Vec<WebhookSink>> {
+    let steam_client = if steam.enabled {
+        Some(SteamClient::new(steam, Some(cache_service))?)
+    } else {
+        None
+    };
+
+    settings
+        .sinks()
+        .iter()
+        .map(|sink| {
+            let sender = build_sender(sink, message, steam_client.clone(), dry_run)
+                .with_context(|| format!("failed to setup webhook sink {}", sink.name))?;
+            Ok(WebhookSink {
+                name: sink.name.clone(),
+                events: sink.events.clone(),
+                sender,
+            })
+        })
+        .collect()
+}
+
+fn build_sender(
+    settings: &WebhookSinkSettings,
+    message: &MessageTemplateSettings,
+    steam_client: Option<SteamClient>,
+    dry_run: bool,
 ) -> Result<Arc<dyn WebhookSender>> {
-    let shared = SharedWebhookClient::new(settings)?;
+    let shared = SharedWebhookClient::new(settings, dry_run)?;
+    let message = settings.message.as_ref().unwrap_or(message);
 
     match settings.mode {
         WebhookMode::OpenclawWake => Ok(Arc::new(OpenClawWakeSender::new(
             shared,
             settings,
             message,
-            steam,
-            cache_service,
+            steam_client,
         )?)),
         WebhookMode::GenericJson => Ok(Arc::new(GenericJsonSender::new(shared))),
     }
@@ -43,22 +94,34 @@ pub fn build_sender(
 pub struct SharedWebhookClient {
     pub client: Client,
     pub url: Url,
+    sink: String,
+    headers: HeaderMap,
+    signer: Option<RequestSigner>,
+    /// Log requests instead of sending them.
+    dry_run: bool,
+}
+
+#[derive(Debug, Clone)]
+struct RequestSigner {
+    secret: Vec<u8>,
+    signature_header: HeaderName,
+    timestamp_header: HeaderName,
 }
 
 impl SharedWebhookClient {
-    fn new(settings: &WebhookSettings) -> Result<Self> {
+    fn new(settings: &WebhookSinkSettings, dry_run: bool) -> Result<Self> {
         let mut headers = HeaderMap::new();
 
         if let Some(token) = settings
             .token
             .as_ref()
+            .map(Secret::expose)
             .filter(|token| !token.trim().is_empty())
         {
-            let bearer = format!("Bearer {token}");
-            headers.insert(
-                AUTHORIZATION,
-                HeaderValue::from_str(&bearer).context("invalid webhook token value")?,
-            );
+            let mut bearer = HeaderValue::from_str(&format!("Bearer {token}"))
+                .context("invalid webhook token value")?;
+            bearer.set_sensitive(true);
+            headers.insert(AUTHORIZATION, bearer);
         }
 
         for (raw_name, raw_value) in &settings.headers {
@@ -70,7 +133,6 @@ impl SharedWebhookClient {
         }
 
         let client = Client::builder()
-            .default_headers(headers)
             .timeout(settings.timeout())
             .build()
             .context("failed to build webhook HTTP client")?;
@@ -78,6 +140,188 @@ impl SharedWebhookClient {
         let url = Url::parse(&settings.url)
             .with_context(|| format!("invalid webhook URL: {}", settings.url))?;
 
-        Ok(Self { client, url })
+        let signer = settings
+            .signing
+            .as_ref()
+            .map(|signing| -> Result<RequestSigner> {
+                Ok(RequestSigner {
+                    secret: signing.secret.expose().as_bytes().to_vec(),
+                    signature_header: HeaderName::from_bytes(signing.signature_header.as_bytes())
+                        .context("invalid webhook signature header name")?,
+                    timestamp_header: HeaderName::from_bytes(signing.timestamp_header.as_bytes())
+                        .context("invalid webhook timestamp header name")?,
+                })
+            })
+            .transpose()?;
+
+        Ok(Self {
+            client,
+            url,
+            sink: settings.name.clone(),
+            headers,
+            signer,
+            dry_run,
+        })
+    }
+
+    /// POSTs `payload` as JSON, signing the exact serialized body when signing is
+    /// configured. In dry-run mode the request is logged instead and `None` is
+    /// returned.
+    pub async fn post_json<T: Serialize + ?Sized>(&self, payload: &T) -> Result<Option<Response>> {
+        let request = self.build_request(payload)?;
+        if self.dry_run {
+            let rendered = render_request(&request);
+            info!(
+                sink = %self.sink,
+                url = %rendered.url,
+                headers = %rendered.format_headers(),
+                body = %rendered.body,
+                "dry run: webhook request not sent"
+            );
+            return Ok(None);
+        }
+
+        self.client
+            .execute(request)
+            .await
+            .map(Some)
+            .context("failed to send webhook request")
+    }
+
+    fn build_request<T: Serialize + ?Sized>(&self, payload: &T) -> Result<Request> {
+        let body = serde_json::to_vec(payload).context("failed to serialize webhook payload")?;
+        let mut request = self
+            .client
+            .post(self.url.clone())
+            .headers(self.headers.clone())
+            .header(CONTENT_TYPE, "application/json");
+
+        if let Some(signer) = self.signer.as_ref() {
+            let timestamp = Utc::now().timestamp();
+            let signature = signing::sign(&signer.secret, timestamp, &body);
+            request = request
+                .header(signer.timestamp_header.clone(), timestamp.to_string())
+                .header(signer.signature_header.clone(), signature);
+        }
+
+        request
+            .body(body)
+            .build()
+            .context("failed to build webhook request")
+    }
+}
+
+/// A webhook request as logged in dry-run mode.
+#[derive(Debug)]
+struct RenderedRequest {
+    url: String,
+    /// `(name, value)` pairs with sensitive values redacted.
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl RenderedRequest {
+    fn format_headers(&self) -> String {
+        self.headers
+            .iter()
+            .map(|(name, value)| format!("{name}: {value}"))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+fn render_request(request: &Request) -> RenderedRequest {
+    let headers = request
+        .headers()
+        .iter()
+        .map(|(name, value)| {
+            let value = if value.is_sensitive() {
+                "<redacted>".into()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            };
+            (name.to_string(), value)
+        })
+        .collect();
+    let body = request
+        .body()
+        .and_then(|body| body.as_bytes())
+        .unwrap_or_default();
+    RenderedRequest {
+        url: request.url().to_string(),
+        headers,
+        body: String::from_utf8_lossy(body).into_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header<'a>(rendered: &'a RenderedRequest, name: &str) -> Option<&'a str> {
+        rendered
+            .headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    #[test]
+    fn dry_run_renders_signed_request() {
+        let settings: WebhookSinkSettings = toml::from_str(
+            r#"
+            name = "wake"
+            url = "http://127.0.0.1:9/hooks/wake"
+            token = "secret-token"
+            headers = { "x-source" = "statushub" }
+            signing = { secret = "signing-secret" }
+            "#,
+        )
+        .expect("sink should parse");
+        let client = SharedWebhookClient::new(&settings, true).expect("client should build");
+
+        let request = client
+            .build_request(&serde_json::json!({ "text": "hello" }))
+            .expect("request should build");
+        let rendered = render_request(&request);
+
+        assert_eq!(rendered.url, "http://127.0.0.1:9/hooks/wake");
+        assert_eq!(rendered.body, r#"{"text":"hello"}"#);
+        assert_eq!(header(&rendered, "authorization"), Some("<redacted>"));
+        assert_eq!(header(&rendered, "x-source"), Some("statushub"));
+        assert_eq!(header(&rendered, "content-type"), Some("application/json"));
+        assert!(!rendered.format_headers().contains("secret-token"));
+
+        let timestamp =
+            header(&rendered, "X-StatusHub-Timestamp").expect("timestamp header should be set");
+        let signature =
+            header(&rendered, "X-StatusHub-Signature").expect("signature header should be set");
+        signing::verify(
+            b"signing-secret",
+            timestamp,
+            signature,
+            rendered.body.as_bytes(),
+            Utc::now().timestamp(),
+            60,
+        )
+        .expect("signature should match the rendered body");
+    }
+
+    #[tokio::test]
+    async fn dry_run_does_not_send() {
+        let settings: WebhookSinkSettings = toml::from_str(
+            r#"
+            name = "wake"
+            url = "http://127.0.0.1:9/hooks/wake"
+            "#,
+        )
+        .expect("sink should parse");
+        let client = SharedWebhookClient::new(&settings, true).expect("client should build");
+
+        let response = client
+            .post_json(&serde_json::json!({ "text": "hello" }))
+            .await
+            .expect("dry run should not fail");
+        assert!(response.is_none());
     }
 }
@@ -1,44 +1,37 @@
-use std::sync::Arc;
-
 use anyhow::{Context, Result, bail};
 use async_trait::async_trait;
 use serde::Serialize;
 use tracing::warn;
 
-use crate::cache::CacheService;
-use crate::config::{MessageTemplateSettings, SteamSettings, WebhookSettings};
-use crate::event::DiscordStatusChangedEvent;
-use crate::steam::SteamClient;
+use crate::config::{MessageTemplateSettings, OpenClawWakeMode, WebhookSinkSettings};
+use crate::event::{DiscordStatusChangedEvent, EventKind, format_elapsed};
+use crate::steam::{SteamClient, SteamGameDetails};
+use crate::webhook::template::MessageRenderer;
 use crate::webhook::{SharedWebhookClient, WebhookSender};
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct OpenClawWakeSender {
     client: SharedWebhookClient,
     wake_mode: &'static str,
     prefix: Option<String>,
     suffix: Option<String>,
+    renderer: MessageRenderer,
     steam_client: Option<SteamClient>,
 }
 
 impl OpenClawWakeSender {
     pub fn new(
         client: SharedWebhookClient,
-        settings: &WebhookSettings,
+        settings: &WebhookSinkSettings,
         message: &MessageTemplateSettings,
-        steam: &SteamSettings,
-        cache_service: Arc<CacheService>,
+        steam_client: Option<SteamClient>,
     ) -> Result<Self> {
-        let steam_client = if steam.enabled {
-            Some(SteamClient::new(steam, Some(cache_service))?)
-        } else {
-            None
-        };
-
         Ok(Self {
             client,
             wake_mode: settings.openclaw.wake_mode.as_str(),
             prefix: normalize_optional_text(message.prefix.clone()),
             suffix: normalize_optional_text(message.suffix.clone()),
+            renderer: MessageRenderer::new(message)?,
             steam_client,
         })
     }
@@ -56,17 +49,21 @@ impl WebhookSender for OpenClawWakeSender {
         let text = self.build_text(event).await;
         let payload = OpenClawWakePayload {
             text: &text,
-            mode: self.wake_mode,
+            mode: if event.quiet_hours {
+                OpenClawWakeMode::NextHeartbeat.as_str()
+            } else {
+                self.wake_mode
+            },
         };
 
-        let response = self
+        let Some(response) = self
             .client
-            .client
-            .post(self.client.url.clone())
-            .json(&payload)
-            .send()
+            .post_json(&payload)
             .await
-            .context("failed to call OpenClaw webhook")?;
+            .context("failed to call OpenClaw webhook")?
+        else {
+            return Ok(());
+        };
 
         if response.status().is_success() {
             return Ok(());
@@ -83,21 +80,40 @@ impl WebhookSender for OpenClawWakeSender {
 
 impl OpenClawWakeSender {
     async fn build_text(&self, event: &DiscordStatusChangedEvent) -> String {
-        let mut parts = Vec::new();
-        if let Some(prefix) = &self.prefix {
-            parts.push(prefix.clone());
-        }
-
-        parts.push(event.to_base_text());
+        let steam_game = self.fetch_steam_game(event).await;
 
+        let mut default_parts = vec![event.to_base_text()];
+        if let Some(custom_status_line) = build_custom_status_section(event) {
+            default_parts.push(custom_status_line);
+        }
         if let Some(activity_line) = build_activity_section(event) {
-            parts.push(activity_line);
+            default_parts.push(activity_line);
         }
-
-        if let Some(steam_line) = self.build_steam_section(event).await {
-            parts.push(steam_line);
+        if let Some(music_line) = build_music_section(event) {
+            default_parts.push(music_line);
         }
+        if let Some(steam_line) = build_steam_section(event, steam_game.as_ref()) {
+            default_parts.push(steam_line);
+        }
+        let default_text = default_parts.join("\n");
+
+        let body = match self
+            .renderer
+            .render(event, steam_game.as_ref(), &default_text)
+        {
+            Ok(Some(rendered)) => rendered,
+            Ok(None) => default_text,
+            Err(err) => {
+                warn!(kind = %event.kind(), error = ?err, "failed to render message template, using default text");
+                default_text
+            }
+        };
 
+        let mut parts = Vec::new();
+        if let Some(prefix) = &self.prefix {
+            parts.push(prefix.clone());
+        }
+        parts.push(body);
         if let Some(suffix) = &self.suffix {
             parts.push(suffix.clone());
         }
@@ -105,31 +121,68 @@ impl OpenClawWakeSender {
         parts.join("\n")
     }
 
-    async fn build_steam_section(&self, event: &DiscordStatusChangedEvent) -> Option<String> {
+    async fn fetch_steam_game(
+        &self,
+        event: &DiscordStatusChangedEvent,
+    ) -> Option<SteamGameDetails> {
         let steam_client = self.steam_client.as_ref()?;
-        let activity = event.activity.as_ref()?;
-        let app_id = activity.steam_app_id?;
-
-        let fallback_name = activity.name.clone();
+        let app_id = event.activity.as_ref()?.steam_app_id?;
 
         match steam_client.fetch_game_details(app_id).await {
-            Ok(Some(game)) => {
-                let mut line = format!("Steam game: {} (app_id={})", game.name, game.app_id);
-                if let Some(desc) = game.short_description {
-                    line.push_str(&format!("\n简介: {desc}"));
-                }
-                if let Some(player_count) = game.current_players {
-                    line.push_str(&format!("\n当前在线人数: {player_count}"));
-                }
-                Some(line)
-            }
-            Ok(None) => Some(format!("Steam game: {} (app_id={})", fallback_name, app_id)),
+            Ok(game) => game,
             Err(err) => {
                 warn!(app_id, error = ?err, "failed to fetch Steam game details");
-                Some(format!("Steam game: {} (app_id={})", fallback_name, app_id))
+                None
+            }
+        }
+    }
+}
+
+fn build_steam_section(
+    event: &DiscordStatusChangedEvent,
+    steam_game: Option<&SteamGameDetails>,
+) -> Option<String> {
+    let activity = event.activity.as_ref()?;
+    let app_id = activity.steam_app_id?;
+
+    match steam_game {
+        Some(game) => {
+            let mut line = format!("Steam game: {} (app_id={})", game.name, game.app_id);
+            if let Some(desc) = game.short_description.as_deref() {
+                line.push_str(&format!("\n简介: {desc}"));
             }
+            if let Some(player_count) = game.current_players {
+                line.push_str(&format!("\n当前在线人数: {player_count}"));
+            }
+            Some(line)
         }
+        None => Some(format!("Steam game: {} (app_id={})", activity.name, app_id)),
+    }
+}
+
+fn build_custom_status_section(event: &DiscordStatusChangedEvent) -> Option<String> {
+    // Custom status change texts already name the new custom status.
+    if event.kind() == EventKind::CustomStatusChange {
+        return None;
+    }
+    let custom_status = event.custom_status.as_ref()?;
+    Some(format!("Custom status: {custom_status}"))
+}
+
+fn build_music_section(event: &DiscordStatusChangedEvent) -> Option<String> {
+    let music = event.music.as_ref()?;
+    let mut line = format!("Music: {music}");
+    if let (Some(position), Some(duration)) = (music.position_seconds, music.duration_seconds) {
+        line.push_str(&format!(
+            "\nProgress: {} / {}",
+            format_elapsed(position),
+            format_elapsed(duration)
+        ));
+    }
+    if let Some(url) = music.track_url.as_deref() {
+        line.push_str(&format!("\nTrack: {url}"));
     }
+    Some(line)
 }
 
 fn build_activity_section(event: &DiscordStatusChangedEvent) -> Option<String> {
@@ -149,6 +202,9 @@ fn build_activity_section(event: &DiscordStatusChangedEvent) -> Option<String> {
     {
         line.push_str(&format!("\nState: {state}"));
     }
+    if let Some(elapsed) = activity.elapsed_seconds(event.observed_at) {
+        line.push_str(&format!("\nElapsed: {}", format_elapsed(elapsed)));
+    }
     Some(line)
 }
 
@@ -179,6 +235,8 @@ mod tests {
                 details: Some("Editing src/main.rs".to_string()),
                 state: Some("Workspace: StatusHub".to_string()),
                 steam_app_id: None,
+                started_at: None,
+                ends_at: None,
             }),
             None,
         );
@@ -0,0 +1,165 @@
+use anyhow::{Context, Result};
+use minijinja::Environment;
+use serde::Serialize;
+
+use crate::config::MessageTemplateSettings;
+use crate::event::{DiscordStatusChangedEvent, EventKind, format_elapsed};
+use crate::steam::SteamGameDetails;
+
+/// Renders user-defined text templates for each event kind.
+///
+/// Templates see every event field at the top level (`user_id`, `current_status`,
+/// `activity.name`, `reminder.sequence`, ...), plus `kind`, `steam` (Steam game
+/// details when available) and `default_text` (the built-in text). The `duration`
+/// filter formats a number of seconds like `1h 5m 0s`.
+#[derive(Debug)]
+pub struct MessageRenderer {
+    env: Environment<'static>,
+}
+
+#[derive(Debug, Serialize)]
+struct TemplateContext<'a> {
+    #[serde(flatten)]
+    event: &'a DiscordStatusChangedEvent,
+    kind: EventKind,
+    steam: Option<&'a SteamGameDetails>,
+    default_text: &'a str,
+}
+
+impl MessageRenderer {
+    pub fn new(message: &MessageTemplateSettings) -> Result<Self> {
+        let mut env = Environment::new();
+        env.add_filter("duration", |seconds: u64| format_elapsed(seconds));
+        for (kind, source) in &message.templates {
+            env.add_template_owned(kind.to_string(), source.clone())
+                .with_context(|| format!("invalid message template for {kind}"))?;
+        }
+        Ok(Self { env })
+    }
+
+    /// Renders the template configured for the event kind, or returns `None` when
+    /// no template exists for it.
+    pub fn render(
+        &self,
+        event: &DiscordStatusChangedEvent,
+        steam: Option<&SteamGameDetails>,
+        default_text: &str,
+    ) -> Result<Option<String>> {
+        let kind = event.kind();
+        let Ok(template) = self.env.get_template(&kind.to_string()) else {
+            return Ok(None);
+        };
+
+        let context = TemplateContext {
+            event,
+            kind,
+            steam,
+            default_text,
+        };
+        let rendered = template
+            .render(&context)
+            .with_context(|| format!("failed to render message template for {kind}"))?;
+        Ok(Some(rendered.trim().to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::event::{DiscordActivityContext, DiscordStatus, ReminderContext};
+
+    fn make_renderer(templates: &[(EventKind, &str)]) -> MessageRenderer {
+        let settings = MessageTemplateSettings {
+            prefix: None,
+            suffix: None,
+            templates: templates
+                .iter()
+                .map(|(kind, source)| (*kind, source.to_string()))
+                .collect::<HashMap<_, _>>(),
+        };
+        MessageRenderer::new(&settings).expect("renderer should build")
+    }
+
+    #[test]
+    fn render_status_change_with_event_fields() {
+        let renderer = make_renderer(&[(
+            EventKind::StatusChange,
+            "{{ user_id }} 从 {{ previous_status }} 变为 {{ current_status }}",
+        )]);
+        let event = DiscordStatusChangedEvent::new(
+            7,
+            None,
+            Some(DiscordStatus::Offline),
+            DiscordStatus::Online,
+            None,
+            None,
+        );
+
+        let text = renderer
+            .render(&event, None, "default")
+            .expect("render should succeed");
+        assert_eq!(text.as_deref(), Some("7 从 offline 变为 online"));
+    }
+
+    #[test]
+    fn render_reminder_with_steam_and_duration() {
+        let renderer = make_renderer(&[(
+            EventKind::Reminder,
+            "{{ activity.name }} {{ reminder.elapsed_seconds | duration }}{% if steam %} / {{ steam.name }}{% endif %}",
+        )]);
+        let event = DiscordStatusChangedEvent::new(
+            7,
+            None,
+            None,
+            DiscordStatus::Online,
+            Some(DiscordActivityContext {
+                name: "Dota 2".to_string(),
+                details: None,
+                state: None,
+                steam_app_id: Some(570),
+                started_at: None,
+                ends_at: None,
+            }),
+            Some(ReminderContext {
+                elapsed_seconds: 3900,
+                interval_seconds: 1800,
+                sequence: 2,
+                activity_elapsed_seconds: None,
+                rule: None,
+                ended: false,
+            }),
+        );
+        let steam = SteamGameDetails {
+            app_id: 570,
+            name: "Dota 2".to_string(),
+            short_description: None,
+            current_players: None,
+        };
+
+        let text = renderer
+            .render(&event, Some(&steam), "default")
+            .expect("render should succeed");
+        assert_eq!(text.as_deref(), Some("Dota 2 1h 5m 0s / Dota 2"));
+    }
+
+    #[test]
+    fn missing_template_falls_back() {
+        let renderer = make_renderer(&[]);
+        let event = DiscordStatusChangedEvent::new(
+            7,
+            None,
+            Some(DiscordStatus::Offline),
+            DiscordStatus::Online,
+            None,
+            None,
+        );
+        assert_eq!(
+            renderer
+                .render(&event, None, "default")
+                .expect("render should succeed"),
+            None
+        );
+    }
+}